@@ -0,0 +1,13 @@
+/// Value of `--name value` or `--name=value` on the command line.
+pub fn arg_value(args: &[String], name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let prefix = format!("{}=", flag);
+
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if *arg == flag {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(&prefix).map(|value| value.to_string())
+        }
+    })
+}
\ No newline at end of file
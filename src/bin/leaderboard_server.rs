@@ -0,0 +1,157 @@
+// Stand-in leaderboard server, so shared leaderboards work without the real one.
+//
+//     cargo run --bin leaderboard_server -- --bind 127.0.0.1:7878 --data leaderboard.toml
+//
+// Runs are kept in memory, and in the `--data` file across restarts when given.
+
+#[path = "../leaderboard/protocol.rs"]
+mod protocol;
+#[path = "../args.rs"]
+mod args;
+
+use std::{env, fs, io, net::{TcpListener, TcpStream}, path::PathBuf, time::Duration};
+
+use args::arg_value;
+use protocol::{RunResult, Scores};
+use serde::{Deserialize, Serialize};
+
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Default)]
+struct Board {
+    #[serde(default)]
+    runs: Vec<RunResult>,
+}
+
+struct Server {
+    board: Board,
+    data_path: Option<PathBuf>,
+}
+
+impl Server {
+    fn handle(&mut self, stream: &TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let request = match protocol::read_message(stream) {
+            Ok(request) => request,
+            Err(e) => return respond(stream, "400 Bad Request", e.to_string()),
+        };
+
+        let mut parts = request.start_line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("POST"), Some("/runs")) => self.submit(stream, &request.body),
+            (Some("GET"), Some(target)) if target.starts_with("/scores") => self.scores(stream, target),
+            _ => respond(stream, "404 Not Found", String::new()),
+        }
+    }
+
+    fn submit(&mut self, stream: &TcpStream, body: &[u8]) -> io::Result<()> {
+        let run: RunResult = match std::str::from_utf8(body).ok().and_then(|body| toml::from_str(body).ok()) {
+            Some(run) => run,
+            None => return respond(stream, "400 Bad Request", "Expected a run".to_string()),
+        };
+        if !run.is_valid() {
+            return respond(stream, "400 Bad Request", "Invalid run".to_string());
+        }
+
+        println!("Run on seed {}: {:.1}s, {:.0} high", run.seed, run.time, run.height);
+        self.board.runs.push(run);
+        self.save();
+
+        respond(stream, "201 Created", String::new())
+    }
+
+    fn scores(&self, stream: &TcpStream, target: &str) -> io::Result<()> {
+        let seed = query_value(target, "seed").and_then(|seed| seed.parse().ok());
+        let limit = query_value(target, "limit")
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(protocol::DEFAULT_LIMIT)
+            .min(protocol::MAX_LIMIT);
+
+        let seed = match seed {
+            Some(seed) => seed,
+            None => return respond(stream, "400 Bad Request", "Expected a seed".to_string()),
+        };
+
+        let mut entries: Vec<RunResult> = self.board.runs
+            .iter()
+            .filter(|run| run.seed == seed)
+            .cloned()
+            .collect();
+        protocol::rank(&mut entries);
+        entries.truncate(limit);
+
+        match toml::to_string(&Scores { seed, entries }) {
+            Ok(body) => respond(stream, "200 OK", body),
+            Err(e) => respond(stream, "500 Internal Server Error", e.to_string()),
+        }
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.data_path {
+            let result = toml::to_string(&self.board)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .and_then(|contents| fs::write(path, contents));
+
+            if let Err(e) = result {
+                eprintln!("Failed to save runs to {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+fn respond(stream: &TcpStream, status: &str, body: String) -> io::Result<()> {
+    protocol::write_message(stream, &format!("HTTP/1.0 {}", status), body.as_bytes())
+}
+
+fn query_value<'a>(target: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = target.split_once('?')?;
+
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let bind = arg_value(&args, "bind").unwrap_or_else(|| protocol::DEFAULT_ADDRESS.to_string());
+    let data_path = arg_value(&args, "data").map(PathBuf::from);
+
+    // A file that can't be read is left alone rather than overwritten
+    let board = match &data_path {
+        Some(path) if path.exists() => {
+            match fs::read_to_string(path).map(|contents| toml::from_str(&contents)) {
+                Ok(Ok(board)) => board,
+                Ok(Err(e)) => exit_with(&format!("Failed to parse {}: {}", path.display(), e)),
+                Err(e) => exit_with(&format!("Failed to read {}: {}", path.display(), e)),
+            }
+        },
+        _ => Board::default(),
+    };
+
+    let listener = match TcpListener::bind(&bind) {
+        Ok(listener) => listener,
+        Err(e) => exit_with(&format!("Failed to listen on {}: {}", bind, e)),
+    };
+    println!("Leaderboard listening on {}", bind);
+
+    let mut server = Server {
+        board,
+        data_path,
+    };
+
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| server.handle(&stream));
+        if let Err(e) = result {
+            eprintln!("Request failed: {}", e);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use crate::{entity::Entity, physics::{BodyDesc, BodyHandle, ColliderDesc, ColliderHandle, ColliderShape, ObjectType, RemovalQueue}, World};
+use nalgebra::{Point2, Vector2};
+
+/// Half extents of the flag's sensor, from the ground up to the top of the pole.
+const FLAG_SIZE: (f32, f32) = (8., 20.);
+
+/// What happens when a player touches the lava in a checkpointed mode.
+#[derive(Debug, Clone, Copy)]
+pub struct RespawnRules {
+    /// Pixels below the checkpoint the lava is pushed back to on a respawn.
+    pub lava_clearance: f32,
+}
+
+impl Default for RespawnRules {
+    fn default() -> Self {
+        Self {
+            lava_clearance: 150.,
+        }
+    }
+}
+
+/// A flag that remembers where a player touched it, to respawn there.
+pub struct Checkpoint {
+    pub entity: Entity,
+    /// Center of the flag, it stands on whatever is below it.
+    pub position: Point2<f32>,
+    pub is_reached: bool,
+
+    body_handle: BodyHandle,
+    collider_handle: ColliderHandle,
+    removal_queue: RemovalQueue,
+}
+
+impl Checkpoint {
+    pub fn new(position: Point2<f32>, world: &mut World) -> Self {
+        let entity = world.entities.spawn(ObjectType::Checkpoint);
+
+        let body_handle = world.physics.add_body(BodyDesc::fixed().position(position));
+
+        let shape = ColliderShape::Cuboid(Vector2::new(FLAG_SIZE.0, FLAG_SIZE.1));
+        let collider_handle = world.physics.add_sensor(
+            body_handle,
+            ColliderDesc::new(shape),
+            entity,
+        ).expect("The body was just added");
+
+        Self {
+            entity,
+            position,
+            is_reached: false,
+            body_handle,
+            collider_handle,
+            removal_queue: world.physics.removal_queue(),
+        }
+    }
+
+    pub fn collider_handle(&self) -> ColliderHandle {
+        self.collider_handle
+    }
+
+    pub fn height(&self) -> f32 {
+        FLAG_SIZE.1 * 2.
+    }
+}
+
+impl Drop for Checkpoint {
+    fn drop(&mut self) {
+        self.removal_queue.remove_body(self.body_handle);
+    }
+}
\ No newline at end of file
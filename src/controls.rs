@@ -0,0 +1,162 @@
+use crate::{KeyCode, player::PlayerInput};
+use ggez::event::{Axis, Button, GamepadId};
+
+/// How far a stick has to be pushed before it counts as a direction.
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+/// Keys that drive one `PlayerInput`, so several players can share a keyboard.
+#[derive(Debug, Clone)]
+pub struct KeySet {
+    pub left: &'static [KeyCode],
+    pub right: &'static [KeyCode],
+    pub up: &'static [KeyCode],
+    pub down: &'static [KeyCode],
+    pub jump: &'static [KeyCode],
+    pub dash: &'static [KeyCode],
+    pub grapple: &'static [KeyCode],
+}
+
+impl KeySet {
+    /// Everything at once, for when one player has the keyboard to themselves.
+    pub fn single() -> Self {
+        Self {
+            left: &[KeyCode::A, KeyCode::Left],
+            right: &[KeyCode::D, KeyCode::Right],
+            up: &[KeyCode::W, KeyCode::Up],
+            down: &[KeyCode::S, KeyCode::Down],
+            jump: &[KeyCode::Space],
+            dash: &[KeyCode::LShift, KeyCode::X],
+            grapple: &[KeyCode::E, KeyCode::C],
+        }
+    }
+
+    pub fn wasd() -> Self {
+        Self {
+            left: &[KeyCode::A],
+            right: &[KeyCode::D],
+            up: &[KeyCode::W],
+            down: &[KeyCode::S],
+            jump: &[KeyCode::Space],
+            dash: &[KeyCode::LShift],
+            grapple: &[KeyCode::E],
+        }
+    }
+
+    pub fn arrows() -> Self {
+        Self {
+            left: &[KeyCode::Left],
+            right: &[KeyCode::Right],
+            up: &[KeyCode::Up],
+            down: &[KeyCode::Down],
+            jump: &[KeyCode::RControl],
+            dash: &[KeyCode::RShift],
+            grapple: &[KeyCode::Return],
+        }
+    }
+
+    /// Returns false if `keycode` isn't part of the set.
+    pub fn apply(&self, input: &mut PlayerInput, keycode: KeyCode, pressed: bool, repeat: bool) -> bool {
+        if self.left.contains(&keycode) {
+            input.left = pressed;
+        } else if self.right.contains(&keycode) {
+            input.right = pressed;
+        } else if self.up.contains(&keycode) {
+            input.up = pressed;
+        } else if self.down.contains(&keycode) {
+            input.down = pressed;
+        } else if self.jump.contains(&keycode) {
+            input.jump = pressed && !repeat;
+        } else if self.dash.contains(&keycode) {
+            input.dash = pressed && !repeat;
+        } else if self.grapple.contains(&keycode) {
+            input.grapple = pressed && !repeat;
+        } else {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// What one local player plays with.
+#[derive(Debug, Clone)]
+pub enum Controls {
+    Keys(KeySet),
+    /// `None` until a gamepad no one has claimed yet presses a button.
+    Gamepad(Option<GamepadId>),
+}
+
+impl Controls {
+    pub fn key(&self, input: &mut PlayerInput, keycode: KeyCode, pressed: bool, repeat: bool) -> bool {
+        match self {
+            Controls::Keys(keys) => keys.apply(input, keycode, pressed, repeat),
+            Controls::Gamepad(_) => false,
+        }
+    }
+
+    /// The d-pad moves, south jumps, west dashes and east grapples.
+    pub fn button(&self, input: &mut PlayerInput, id: GamepadId, button: Button, pressed: bool) -> bool {
+        if !self.uses_gamepad(id) {
+            return false;
+        }
+
+        match button {
+            Button::DPadLeft => input.left = pressed,
+            Button::DPadRight => input.right = pressed,
+            Button::DPadUp => input.up = pressed,
+            Button::DPadDown => input.down = pressed,
+            Button::South => input.jump = pressed,
+            Button::West => input.dash = pressed,
+            Button::East => input.grapple = pressed,
+            _ => (),
+        };
+
+        true
+    }
+
+    pub fn axis(&self, input: &mut PlayerInput, id: GamepadId, axis: Axis, value: f32) -> bool {
+        if !self.uses_gamepad(id) {
+            return false;
+        }
+
+        // The stick's y axis points up
+        match axis {
+            Axis::LeftStickX => {
+                input.left = value < -STICK_DEAD_ZONE;
+                input.right = value > STICK_DEAD_ZONE;
+            },
+            Axis::LeftStickY => {
+                input.up = value > STICK_DEAD_ZONE;
+                input.down = value < -STICK_DEAD_ZONE;
+            },
+            _ => (),
+        };
+
+        true
+    }
+
+    pub fn uses_gamepad(&self, id: GamepadId) -> bool {
+        match self {
+            Controls::Gamepad(claimed) => *claimed == Some(id),
+            Controls::Keys(_) => false,
+        }
+    }
+
+    pub fn is_waiting_for_gamepad(&self) -> bool {
+        match self {
+            Controls::Gamepad(claimed) => claimed.is_none(),
+            Controls::Keys(_) => false,
+        }
+    }
+
+    /// Binds the gamepad if these controls are still waiting for one.
+    pub fn claim_gamepad(&mut self, id: GamepadId) -> bool {
+        match self {
+            Controls::Gamepad(claimed @ None) => {
+                *claimed = Some(id);
+                true
+            },
+            _ => false,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+use crate::physics::ObjectType;
+
+/// Identifies one game entity, no matter how many colliders it is made of.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct EntityId(u32);
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Entity {
+    pub id: EntityId,
+    pub kind: ObjectType,
+}
+
+#[derive(Default)]
+pub struct EntityAllocator {
+    next_id: u32,
+}
+
+impl EntityAllocator {
+    pub fn spawn(&mut self, kind: ObjectType) -> Entity {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+
+        Entity {
+            id,
+            kind,
+        }
+    }
+}
\ No newline at end of file
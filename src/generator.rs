@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// SplitMix64, small and the same on every platform, so a seed builds the same
+/// level for everyone.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: u64::from(seed),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// In `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fit an f32 exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// In `[min, max)`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// In `[0, n)`, `n` can't be 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+/// The UTC date as `yyyymmdd`, so everyone playing on the same day gets the same seed.
+pub fn daily_seed(now: SystemTime) -> u32 {
+    let days = now
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year * 10_000 + month * 100 + day) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn daily_seeds_are_utc_dates() {
+        assert_eq!(daily_seed(UNIX_EPOCH), 19_700_101);
+        assert_eq!(daily_seed(UNIX_EPOCH + Duration::from_secs(951_782_400)), 20_000_229);
+    }
+
+    /// Levels already out there depend on this sequence, it must never change.
+    #[test]
+    fn a_seed_always_gives_the_same_numbers() {
+        let mut rng = Rng::new(20_261_019);
+
+        assert_eq!(rng.next_f32(), 0.322_890_22);
+        assert_eq!(rng.range(-3., 3.), 2.804_502_5);
+        assert_eq!(rng.below(4), 1);
+    }
+}
\ No newline at end of file
@@ -24,6 +24,7 @@ pub struct PawnDrawData {
     pub position: Point2<f32>,
     pub rotation: f32,
     pub pawn_type: DrawPawnType,
+    pub tint: graphics::Color,
 }
 
 impl Sprite {
@@ -45,7 +46,7 @@ pub struct GfxUtil {
 impl GfxUtil {
     pub fn new(ctx: &mut Context) -> Self {
         Self{
-            player_sprite: Sprite::new(ctx, "/sprites/player.png".to_string()),
+            player_sprite: Sprite::new(ctx, "/player.png".to_string()),
         }
     }
 
@@ -59,23 +60,22 @@ impl GfxUtil {
 
             let (image_width, image_height) = self.player_sprite.dimension;
 
+            // Same offset the level draws everything else with
             let position = pawn.position;
-            let dest = Point2::new(position.x, position.y);
+            let dest = util::point_to_old(Point2::new(position.x + 100., position.y + 100.));
 
-            let dest = util::point_to_old(Point2::new(
-                dest.x + ((image_width as f32 + PAWN_RADIUS) / 2.), 
-                dest.y + ((image_height as f32 + PAWN_RADIUS) / 2.),
-            ));
             let scale = util::vector_to_old(Vector2::new(
                 (PAWN_RADIUS / image_width as f32) * 2.,
                 (PAWN_RADIUS / image_height as f32) * 2.,
             ));
-            let offset = util::point_to_old(Point2::new(-0.5, -0.5));
+            let offset = util::point_to_old(Point2::new(0.5, 0.5));
 
             let param = graphics::DrawParam::default()
             .dest(dest)
+            .rotation(pawn.rotation)
             .scale(scale)
-            .offset(offset);
+            .offset(offset)
+            .color(pawn.tint);
 
             image.draw(ctx, param)
                 .unwrap();
@@ -0,0 +1,109 @@
+use crate::{snapshot::LavaSnapshot, power_up::PowerUpEffect, physics::{BodyDesc, BodyHandle, ColliderDesc, ColliderHandle, ColliderShape, ObjectType, RemovalQueue}, World};
+use nalgebra::{Point2, Vector2};
+
+/// How far the lava reaches below its surface.
+const LAVA_DEPTH: f32 = 1000.;
+
+pub struct Lava {
+    /// Y coordinate of the surface, in pixels.
+    pub height: f32,
+    /// Pixels per second the surface moves up.
+    pub rise_speed: f32,
+    /// Centered on x = 0, like the level's floor.
+    pub width: f32,
+    /// Seconds left of a time freeze, the surface holds still until then.
+    pub frozen_for: f32,
+
+    body_handle: BodyHandle,
+    collider_handle: ColliderHandle,
+    removal_queue: RemovalQueue,
+}
+
+impl Lava {
+    pub fn new(
+        height: f32,
+        width: f32,
+        rise_speed: f32,
+        world: &mut World,
+    ) -> Self {
+        let entity = world.entities.spawn(ObjectType::Lava);
+
+        let body_desc = BodyDesc::kinematic()
+            .position(Self::center(height));
+
+        let body_handle = world.physics.add_body(body_desc);
+
+        let shape = ColliderShape::Cuboid(Vector2::new(width / 2., LAVA_DEPTH / 2.));
+        let collider_desc = ColliderDesc::new(shape);
+
+        let collider_handle = world.physics.add_sensor(
+            body_handle,
+            collider_desc,
+            entity,
+        ).expect("The body was just added");
+
+        Self {
+            height,
+            rise_speed,
+            width,
+            frozen_for: 0.,
+            body_handle,
+            collider_handle,
+            removal_queue: world.physics.removal_queue(),
+        }
+    }
+
+    pub fn collider_handle(&self) -> ColliderHandle {
+        self.collider_handle
+    }
+
+    pub fn update(&mut self, dt: f32, world: &mut World) {
+        if self.is_frozen() {
+            self.frozen_for = (self.frozen_for - dt).max(0.);
+            return;
+        }
+
+        self.height -= self.rise_speed * dt;
+        world.physics.set_position(self.body_handle, Self::center(self.height));
+    }
+
+    pub fn snapshot(&self) -> LavaSnapshot {
+        LavaSnapshot {
+            height: self.height,
+            rise_speed: self.rise_speed,
+            frozen_for: self.frozen_for,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &LavaSnapshot, world: &mut World) {
+        self.height = snapshot.height;
+        self.rise_speed = snapshot.rise_speed;
+        self.frozen_for = snapshot.frozen_for;
+        world.physics.set_position(self.body_handle, Self::center(self.height));
+    }
+
+    /// Moves the surface down to `height`, never up.
+    pub fn push_back(&mut self, height: f32, world: &mut World) {
+        self.height = self.height.max(height);
+        world.physics.set_position(self.body_handle, Self::center(self.height));
+    }
+
+    /// Stops the rise for the effect's duration, stacked with any freeze still going.
+    pub fn freeze(&mut self, effect: &PowerUpEffect) {
+        self.frozen_for = effect.stacking.apply(self.frozen_for, effect.duration);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_for > 0.
+    }
+
+    fn center(height: f32) -> Point2<f32> {
+        Point2::new(0., height + LAVA_DEPTH / 2.)
+    }
+}
+
+impl Drop for Lava {
+    fn drop(&mut self) {
+        self.removal_queue.remove_body(self.body_handle);
+    }
+}
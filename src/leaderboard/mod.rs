@@ -0,0 +1,172 @@
+pub mod protocol;
+
+pub use self::protocol::{RunResult, Scores};
+
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+/// Unsent runs kept for a retry, the oldest are dropped past this.
+const MAX_PENDING: usize = 100;
+
+enum Request {
+    Submit(RunResult),
+    Top { seed: u32, limit: usize },
+}
+
+#[derive(Debug)]
+pub enum LeaderboardEvent {
+    /// Every run submitted so far has reached the server.
+    Submitted,
+    Scores(Scores),
+    /// The server couldn't be reached, submitted runs are kept for the next try.
+    Unreachable(String),
+}
+
+/// Talks to a leaderboard server on a thread of its own, so a slow or missing
+/// server never holds up a frame. Requests go out in order and their results
+/// come back through `poll`.
+pub struct LeaderboardClient {
+    requests: Sender<Request>,
+    events: Receiver<LeaderboardEvent>,
+}
+
+impl LeaderboardClient {
+    /// `address` is `host:port`, the server is only contacted once there is something to do.
+    pub fn new(address: String) -> Self {
+        let (requests, requests_receiver) = mpsc::channel();
+        let (events_sender, events) = mpsc::channel();
+
+        // Ends once the client is dropped and the channel closes
+        thread::spawn(move || {
+            let mut worker = Worker {
+                address,
+                pending: Vec::new(),
+                events: events_sender,
+            };
+
+            for request in requests_receiver {
+                worker.handle(request);
+            }
+        });
+
+        Self {
+            requests,
+            events,
+        }
+    }
+
+    pub fn submit(&self, run: RunResult) {
+        self.requests.send(Request::Submit(run)).ok();
+    }
+
+    pub fn fetch_top(&self, seed: u32, limit: usize) {
+        self.requests.send(Request::Top { seed, limit }).ok();
+    }
+
+    /// Everything that came back since the last call, never blocks.
+    pub fn poll(&self) -> Vec<LeaderboardEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+struct Worker {
+    address: String,
+    /// Runs the server hasn't taken yet, oldest first.
+    pending: Vec<RunResult>,
+    events: Sender<LeaderboardEvent>,
+}
+
+impl Worker {
+    fn handle(&mut self, request: Request) {
+        let event = match request {
+            Request::Submit(run) => {
+                self.pending.push(run);
+                if self.pending.len() > MAX_PENDING {
+                    self.pending.remove(0);
+                }
+
+                match self.flush() {
+                    Ok(()) => LeaderboardEvent::Submitted,
+                    Err(e) => LeaderboardEvent::Unreachable(e.to_string()),
+                }
+            },
+            // Runs still waiting go first, so they show up in the scores
+            Request::Top { seed, limit } => match self.flush().and_then(|_| self.top(seed, limit)) {
+                Ok(scores) => LeaderboardEvent::Scores(scores),
+                Err(e) => LeaderboardEvent::Unreachable(e.to_string()),
+            },
+        };
+
+        self.events.send(event).ok();
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        while let Some(run) = self.pending.first() {
+            let body = toml::to_string(run).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let response = self.request("POST /runs", body.as_bytes())?;
+
+            // Refused runs won't be taken on a retry either
+            if !response.start_line.contains(" 201 ") && !response.start_line.contains(" 400 ") {
+                return Err(io::Error::new(io::ErrorKind::Other, response.start_line));
+            }
+            self.pending.remove(0);
+        }
+
+        Ok(())
+    }
+
+    fn top(&self, seed: u32, limit: usize) -> io::Result<Scores> {
+        let response = self.request(&format!("GET /scores?seed={}&limit={}", seed, limit), &[])?;
+        if !response.start_line.contains(" 200 ") {
+            return Err(io::Error::new(io::ErrorKind::Other, response.start_line));
+        }
+
+        let body = String::from_utf8(response.body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        toml::from_str(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn request(&self, target: &str, body: &[u8]) -> io::Result<protocol::Message> {
+        let address = self.address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No address for the leaderboard"))?;
+
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        protocol::write_message(&stream, &format!("{} HTTP/1.0", target), body)?;
+        protocol::read_message(&stream)
+    }
+}
+
+/// FNV-1a over everything that went into a run, the same inputs on the same
+/// seed always give the same hash.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayHash(u64);
+
+impl Default for ReplayHash {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl ReplayHash {
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+// What the game and the leaderboard server say to each other, shared with
+// `src/bin/leaderboard_server.rs`. Bodies are TOML over plain HTTP/1.0, one
+// request per connection.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// Where the bundled server listens unless told otherwise.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+/// Bodies past this are refused, nothing legitimate comes close.
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+/// Entries returned when a request doesn't ask for a number.
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 100;
+
+/// `POST /runs`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RunResult {
+    /// The level's seed, runs only compete with others on the same one.
+    pub seed: u32,
+    /// Seconds survived.
+    pub time: f32,
+    /// Pixels above the floor.
+    pub height: f32,
+    /// Hex digest of the inputs, to tell runs apart and check them later.
+    pub replay_hash: String,
+}
+
+impl RunResult {
+    pub fn is_valid(&self) -> bool {
+        self.time.is_finite()
+            && self.time >= 0.
+            && self.height.is_finite()
+            && !self.replay_hash.is_empty()
+            && self.replay_hash.len() <= 64
+            && self.replay_hash.chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+/// `GET /scores?seed=<seed>&limit=<limit>`, best first.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Scores {
+    pub seed: u32,
+    #[serde(default)]
+    pub entries: Vec<RunResult>,
+}
+
+/// Longest survival first, the higher climb breaks ties.
+pub fn rank(runs: &mut [RunResult]) {
+    runs.sort_by(|a, b| {
+        b.time
+            .partial_cmp(&a.time)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.height.partial_cmp(&a.height).unwrap_or(std::cmp::Ordering::Equal))
+    });
+}
+
+/// A request or response, the start line is whatever comes before the headers.
+#[derive(Debug)]
+pub struct Message {
+    pub start_line: String,
+    pub body: Vec<u8>,
+}
+
+/// Reads the start line, skips every header but the body's length and reads the body.
+pub fn read_message(stream: impl Read) -> io::Result<Message> {
+    let mut reader = BufReader::new(stream);
+
+    let mut start_line = String::new();
+    reader.read_line(&mut start_line)?;
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Bad content length")
+                })?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Body too large"));
+    }
+
+    let mut body = vec!{0; content_length};
+    reader.read_exact(&mut body)?;
+
+    Ok(Message {
+        start_line: start_line.trim_end().to_string(),
+        body,
+    })
+}
+
+pub fn write_message(mut stream: impl Write, start_line: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "{}\r\nContent-Type: application/toml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        start_line,
+        body.len(),
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
\ No newline at end of file
@@ -0,0 +1,35 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Used unless `LAVA_LOG` asks for something else, e.g. `LAVA_LOG=debug` or `LAVA_LOG=off`.
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Diagnostics go to stderr, anything the player should see goes through `Notices` instead.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Dependencies only get a say when something goes wrong
+        metadata.target().starts_with(env!("CARGO_PKG_NAME")) || metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init() {
+    let level = std::env::var("LAVA_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(DEFAULT_LEVEL);
+
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
\ No newline at end of file
@@ -1,19 +1,34 @@
 #![allow(dead_code)]
 
-use std::{env, path, time::Duration};
+use std::{env, fmt::Display, net::SocketAddr, path, str::FromStr, time::Duration};
 
 use ggez::*;
 use ggez::{ContextBuilder, Context, GameResult};
-use ggez::event::{EventHandler, KeyCode, KeyMods};
+use ggez::event::{Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods};
 
 extern crate nalgebra as na;
 use na::Point2;
 
 mod gfx;
 mod util;
+mod logging;
+mod notice;
 mod physics;
+mod entity;
 mod platform;
 mod player;
+mod movement;
+mod lava;
+mod checkpoint;
+mod generator;
+mod pickup;
+mod power_up;
+mod save;
+mod score;
+mod snapshot;
+mod controls;
+mod net;
+mod leaderboard;
 
 mod scenes;
 use scenes::*;
@@ -21,9 +36,13 @@ use scenes::*;
 mod world;
 use world::*;
 
+mod args;
+use args::arg_value;
+
 pub struct MainState {
     world: World,
     current_scene: Box<dyn Scene<World>>,
+    movement_watcher: movement::ProfileWatcher,
     ticks: usize,
     dt: Duration,
 }
@@ -35,9 +54,25 @@ impl EventHandler for MainState {
     ) -> GameResult<()> {
         self.dt = timer::delta(ctx);
 
+        // Changes made in the meantime are picked up once the scene allows it again
+        if self.current_scene.allows_hot_reload() {
+            match self.movement_watcher.poll(&mut self.world.movement) {
+                Ok(true) => {
+                    self.world.apply_movement_profile();
+                    self.world.notices.show("Reloaded the movement profile");
+                },
+                Ok(false) => (),
+                Err(e) => {
+                    log::warn!("Failed to load movement profile: {}", e);
+                    self.world.notices.show("Failed to load the movement profile, keeping the old one");
+                },
+            }
+        }
+
         if let Some(next_scene) = self.current_scene.update(ctx, &mut self.world) {
             self.current_scene = next_scene
         } 
+        self.world.notices.update(self.dt.as_secs_f32());
 
         Ok(())
     }
@@ -55,6 +90,8 @@ impl EventHandler for MainState {
         graphics::set_screen_coordinates(ctx, new_rect).unwrap();
         graphics::apply_transformations(ctx).unwrap();
 
+        self.world.notices.draw(ctx);
+
         // Draw scene name
         //draw_current_scene_text(ctx, self.current_scene.name());
 
@@ -86,6 +123,34 @@ impl EventHandler for MainState {
     ) {
         self.current_scene.input(&mut self.world, keycode, false, false);    
     }
+
+    fn gamepad_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        id: GamepadId
+    ) {
+        self.current_scene.gamepad_button(&mut self.world, id, button, true);
+    }
+
+    fn gamepad_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        id: GamepadId
+    ) {
+        self.current_scene.gamepad_button(&mut self.world, id, button, false);
+    }
+
+    fn gamepad_axis_event(
+        &mut self,
+        _ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        id: GamepadId
+    ) {
+        self.current_scene.gamepad_axis(&mut self.world, id, axis, value);
+    }
 }
 
 // FIXME: Very slow
@@ -110,7 +175,71 @@ fn draw_current_scene_text(ctx: &mut Context, text: &str) {
     ).unwrap();
 }
 
+fn parse_arg<T>(args: &[String], name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    arg_value(args, name).map(|value| value.parse().unwrap_or_else(|error| {
+        exit_with(&format!("Invalid --{} \"{}\": {}", name, value, error))
+    }))
+}
+
+fn exit_with(message: &str) -> ! {
+    log::error!("{}", message);
+    std::process::exit(1)
+}
+
+/// `--verify-replay <path>` plays a daily challenge's replay back without opening a
+/// window, and exits with an error unless it ends with the time and height it claims.
+fn verify_replay(path: &str) -> ! {
+    let replay = save::Replay::load(path::Path::new(path))
+        .unwrap_or_else(|e| exit_with(&format!("Failed to load replay {}: {}", path, e)));
+
+    match scenes::level::verify_replay(&replay) {
+        Ok(()) => {
+            log::info!("Replay checks out, {:.2}s and {:.1} high", replay.time, replay.height);
+            std::process::exit(0)
+        },
+        Err(e) => exit_with(&format!("Replay doesn't check out: {}", e)),
+    }
+}
+
+/// `--netplay loopback [--latency <frames>]` races two local peers through a loopback
+/// transport, `--netplay udp --bind <addr> --peer <addr> --player <0|1>` races over UDP.
+fn initial_scene(
+    ctx: &mut Context,
+    args: &[String],
+    physics: physics::PhysicsKind,
+    world: &World,
+) -> Box<dyn Scene<World>> {
+    match arg_value(args, "netplay").as_deref() {
+        None => Box::new(scenes::menu::MenuScene::new(ctx)),
+        Some("loopback") => {
+            let latency = parse_arg(args, "latency").unwrap_or(0);
+            Box::new(scenes::netplay::NetplayScene::loopback(ctx, physics, latency, world))
+        },
+        Some("udp") => {
+            let required = |name| parse_arg::<String>(args, name)
+                .unwrap_or_else(|| exit_with(&format!("--netplay udp needs --{}", name)));
+            let bind: SocketAddr = required("bind").parse().unwrap_or_else(|e| exit_with(&format!("Invalid --bind: {}", e)));
+            let peer: SocketAddr = required("peer").parse().unwrap_or_else(|e| exit_with(&format!("Invalid --peer: {}", e)));
+            let player = match parse_arg(args, "player").unwrap_or(0) {
+                player @ 0..=1 => player,
+                _ => exit_with("--player has to be 0 or 1"),
+            };
+
+            let transport = net::UdpTransport::bind(bind, peer)
+                .unwrap_or_else(|e| exit_with(&format!("Failed to bind {}: {}", bind, e)));
+            Box::new(scenes::netplay::NetplayScene::new(ctx, physics, Box::new(transport), player, world))
+        },
+        Some(other) => exit_with(&format!("Unknown netplay mode \"{}\", expected loopback or udp", other)),
+    }
+}
+
 fn main() {
+    logging::init();
+
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
         path.push("resources");
@@ -119,6 +248,14 @@ fn main() {
         path::PathBuf::from("./resources")
     };
 
+    let movement_path = resource_dir.join("movement.toml");
+    let power_ups_path = resource_dir.join("powerups.toml");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(path) = arg_value(&args, "verify-replay") {
+        verify_replay(&path);
+    }
+    let physics = parse_arg(&args, "physics").unwrap_or_default();
+
     let c = conf::Conf::new();
     let (ref mut ctx, ref mut event_loop) = ContextBuilder::new("lava_floor", "troligtvis")
         .add_resource_path(resource_dir)
@@ -127,11 +264,38 @@ fn main() {
         .unwrap();
 
 
-    let initial_screen = Box::new(scenes::menu::MenuScene::new(ctx));
+    // Netplay peers copy the profile when they start, so it has to be loaded by then
+    let mut world = World::new(physics);
+    let mut movement_watcher = movement::ProfileWatcher::new(movement_path);
+    match movement_watcher.poll(&mut world.movement) {
+        Ok(true) => world.apply_movement_profile(),
+        Ok(false) => (),
+        Err(e) => {
+            log::warn!("Failed to load movement profile, using the defaults: {}", e);
+            world.notices.show("Failed to load the movement profile, using the defaults");
+        },
+    }
+    match power_up::PowerUpTable::load(&power_ups_path) {
+        Ok(power_ups) => world.power_ups = power_ups,
+        Err(e) => {
+            log::warn!("Failed to load power-ups, using the defaults: {}", e);
+            world.notices.show("Failed to load the power-ups, using the defaults");
+        },
+    }
+    world.save = save::SaveData::load(&save::SaveData::path(ctx));
+    // Falls back to the bundled server, runs wait in memory while it isn't running
+    world.leaderboard = match arg_value(&args, "leaderboard").as_deref() {
+        Some("off") => None,
+        Some(address) => Some(leaderboard::LeaderboardClient::new(address.to_string())),
+        None => Some(leaderboard::LeaderboardClient::new(leaderboard::protocol::DEFAULT_ADDRESS.to_string())),
+    };
+
+    let initial_screen = initial_scene(ctx, &args, physics, &world);
     
     let state = &mut MainState {
-        world: World::new(),
+        world,
         current_scene: initial_screen,
+        movement_watcher,
         dt: std::time::Duration::new(0, 0),
         ticks: 0usize,
     };
@@ -0,0 +1,181 @@
+use std::{fs, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
+
+use ggez::{GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Every value that decides how the player moves, so the feel can be tuned
+/// from `movement.toml` without recompiling.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct MovementProfile {
+    pub max_vel: f32,
+    pub air_max_vel: f32,
+    pub ground_acceleration: f32,
+    pub ground_deceleration: f32,
+    pub air_acceleration: f32,
+    pub air_deceleration: f32,
+    /// Acceleration multiplier while pushing against the current direction of travel.
+    pub turn_around_multiplier: f32,
+    pub fall_multiplier: f32,
+    pub low_jump_multiplier: f32,
+    pub jump_power: f32,
+    /// World gravity, handed to the physics when a loaded profile changes it.
+    pub gravity: f32,
+    /// Steepest slope in degrees the player can stand on, steeper is a wall.
+    pub max_slope: f32,
+
+    /// Seconds after walking off a ledge during which a jump is still allowed.
+    pub coyote_time: f32,
+    /// Seconds a jump press is remembered before the player lands.
+    pub jump_buffer_time: f32,
+
+    pub wall_slide_speed: f32,
+    pub wall_jump_power: f32,
+    pub wall_jump_kick: f32,
+    pub wall_jump_lock_time: f32,
+
+    pub double_jump_power: f32,
+    pub dash_speed: f32,
+    pub dash_duration: f32,
+    pub dash_cooldown: f32,
+
+    /// How far the grappling hook reaches, and the rope's maximum length.
+    pub grapple_range: f32,
+    pub grapple_min_length: f32,
+    /// Pixels per second the rope is reeled in or out.
+    pub grapple_reel_speed: f32,
+    /// Horizontal acceleration from input while hanging from the rope.
+    pub swing_acceleration: f32,
+
+    /// Seconds of wind acceleration added to the speed the player steers towards,
+    /// without it steering cancels sideways wind every frame.
+    pub wind_carry: f32,
+}
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        Self::snappy()
+    }
+}
+
+impl MovementProfile {
+    /// Instant acceleration and stopping, velocity snaps straight to the target.
+    pub fn snappy() -> Self {
+        Self {
+            max_vel: 100.,
+            air_max_vel: 80.,
+            ground_acceleration: f32::INFINITY,
+            ground_deceleration: f32::INFINITY,
+            air_acceleration: f32::INFINITY,
+            air_deceleration: f32::INFINITY,
+            turn_around_multiplier: 1.,
+            fall_multiplier: 12.5,
+            low_jump_multiplier: 12.,
+            jump_power: 20.,
+            gravity: 30.,
+            max_slope: 45.,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            wall_slide_speed: 15.,
+            wall_jump_power: 20.,
+            wall_jump_kick: 100.,
+            wall_jump_lock_time: 0.15,
+            double_jump_power: 16.,
+            dash_speed: 250.,
+            dash_duration: 0.15,
+            dash_cooldown: 0.5,
+            grapple_range: 200.,
+            grapple_min_length: 20.,
+            grapple_reel_speed: 80.,
+            swing_acceleration: 150.,
+            wind_carry: 0.5,
+        }
+    }
+
+    /// Builds up speed over a few frames and keeps some momentum in the air.
+    pub fn smooth() -> Self {
+        Self {
+            ground_acceleration: 800.,
+            ground_deceleration: 1000.,
+            air_acceleration: 400.,
+            air_deceleration: 200.,
+            turn_around_multiplier: 2.,
+            ..Self::snappy()
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "snappy" => Some(Self::snappy()),
+            "smooth" => Some(Self::smooth()),
+            _ => None,
+        }
+    }
+
+    /// The optional `preset` key picks the base values, every other key
+    /// overrides them. Without a preset, missing keys fall back to the defaults.
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let contents = fs::read_to_string(path)?;
+        let overrides: toml::value::Table = toml::from_str(&contents)?;
+
+        let base = match overrides.get("preset") {
+            Some(toml::Value::String(name)) => Self::preset(name).ok_or_else(|| {
+                GameError::ConfigError(format!("Unknown movement preset \"{}\"", name))
+            })?,
+            Some(_) => return Err(GameError::ConfigError("preset must be a string".to_string())),
+            None => Self::default(),
+        };
+
+        let mut values = toml::Value::try_from(base)?;
+        if let toml::Value::Table(table) = &mut values {
+            table.extend(overrides);
+        }
+
+        Ok(values.try_into()?)
+    }
+}
+
+/// Reloads a `MovementProfile` whenever its file changes on disk.
+pub struct ProfileWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    last_poll: Option<Instant>,
+}
+
+impl ProfileWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_modified: None,
+            last_poll: None,
+        }
+    }
+
+    /// Returns true if `profile` was replaced. A file that fails to parse is an
+    /// error and keeps the old profile, so a typo doesn't break a running game.
+    pub fn poll(&mut self, profile: &mut MovementProfile) -> GameResult<bool> {
+        if let Some(last_poll) = self.last_poll {
+            if last_poll.elapsed() < POLL_INTERVAL {
+                return Ok(false);
+            }
+        }
+        self.last_poll = Some(Instant::now());
+
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return Ok(false),
+        };
+
+        if self.last_modified == Some(modified) {
+            return Ok(false);
+        }
+        self.last_modified = Some(modified);
+
+        *profile = MovementProfile::load(&self.path)?;
+        log::info!("Loaded movement profile from {}", self.path.display());
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,58 @@
+use super::Transport;
+
+use std::{cell::RefCell, collections::VecDeque, io, rc::Rc};
+
+/// Packets in flight, with how many more `receive` calls they wait before arriving.
+type Queue = Rc<RefCell<VecDeque<(usize, Vec<u8>)>>>;
+
+/// Connects two peers in the same process, to try netplay on one machine.
+pub struct LoopbackTransport {
+    outgoing: Queue,
+    incoming: Queue,
+    /// Number of `receive` calls a packet takes to arrive, fakes network lag.
+    latency: usize,
+}
+
+impl LoopbackTransport {
+    /// Both ends of one connection.
+    pub fn pair(latency: usize) -> (Self, Self) {
+        let a: Queue = Default::default();
+        let b: Queue = Default::default();
+
+        (
+            Self {
+                outgoing: a.clone(),
+                incoming: b.clone(),
+                latency,
+            },
+            Self {
+                outgoing: b,
+                incoming: a,
+                latency,
+            },
+        )
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.outgoing.borrow_mut().push_back((self.latency, packet.to_vec()));
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let mut incoming = self.incoming.borrow_mut();
+        let mut packets = Vec::new();
+
+        // Sent in order with the same latency, so the ones that are due are always in front
+        while incoming.front().map(|(wait, _)| *wait == 0).unwrap_or(false) {
+            packets.extend(incoming.pop_front().map(|(_, packet)| packet));
+        }
+        for (wait, _) in incoming.iter_mut() {
+            *wait -= 1;
+        }
+
+        Ok(packets)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,17 @@
+mod loopback;
+mod udp;
+mod rollback;
+
+pub use self::loopback::LoopbackTransport;
+pub use self::udp::UdpTransport;
+pub use self::rollback::RollbackSession;
+
+use std::io;
+
+/// Moves packets between two peers. Delivery is unreliable and unordered,
+/// whoever uses it has to cope with lost and repeated packets.
+pub trait Transport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+    /// Every packet that arrived since the last call, never blocks.
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>>;
+}
\ No newline at end of file
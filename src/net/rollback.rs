@@ -0,0 +1,263 @@
+use super::Transport;
+use crate::player::PlayerInput;
+
+use std::{collections::BTreeMap, convert::TryInto};
+
+/// Frames a peer may run ahead of the last input it has from the other.
+pub const MAX_PREDICTION: u32 = 8;
+/// Acknowledged frame and first frame of the inputs that follow, both `u32`.
+const HEADER_SIZE: usize = 8;
+/// One byte per input, capped so a packet always fits.
+const MAX_INPUTS_PER_PACKET: u32 = 255;
+
+/// Keeps the inputs of a two player game in step over a `Transport`.
+///
+/// Remote inputs that haven't arrived yet are predicted to stay what they were
+/// last, so the game never waits on the network. When the real input turns out
+/// different, `poll` reports the frame to roll back to and the game resimulates
+/// from there with `inputs`.
+pub struct RollbackSession {
+    transport: Box<dyn Transport>,
+    local_player: usize,
+    /// Next frame to simulate.
+    frame: u32,
+    local_inputs: BTreeMap<u32, PlayerInput>,
+    remote_inputs: BTreeMap<u32, PlayerInput>,
+    /// Remote inputs are known for every frame before this one.
+    confirmed: u32,
+    /// The remote peer has our inputs for every frame before this one.
+    acknowledged: u32,
+    /// Remote inputs simulated frames guessed before the real ones came in.
+    predictions: BTreeMap<u32, PlayerInput>,
+}
+
+impl RollbackSession {
+    /// `local_player` is 0 or 1, and the other one on the remote peer.
+    pub fn new(transport: Box<dyn Transport>, local_player: usize) -> Self {
+        Self {
+            transport,
+            local_player,
+            frame: 0,
+            local_inputs: BTreeMap::new(),
+            remote_inputs: BTreeMap::new(),
+            confirmed: 0,
+            acknowledged: 0,
+            predictions: BTreeMap::new(),
+        }
+    }
+
+    pub fn local_player(&self) -> usize {
+        self.local_player
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Every frame before this one has been simulated with the real inputs, or will be
+    /// once the rollback `poll` returned is done. Those frames won't change again.
+    pub fn confirmed_frame(&self) -> u32 {
+        self.confirmed
+    }
+
+    /// False while too far ahead of the remote peer, the game should wait for it.
+    pub fn can_advance(&self) -> bool {
+        self.frame < self.confirmed + MAX_PREDICTION
+    }
+
+    /// Takes in the packets that arrived since the last call. Returns the first frame
+    /// that was simulated with a wrong prediction, it and every frame after it have to
+    /// be simulated again.
+    pub fn poll(&mut self) -> Option<u32> {
+        let packets = self.transport.receive().unwrap_or_else(|e| {
+            log::warn!("Failed to receive inputs: {}", e);
+            Vec::new()
+        });
+
+        // Inputs before the last rollback are done with, only the last remote one
+        // is kept around to predict from
+        let oldest = self.confirmed.saturating_sub(1);
+        self.remote_inputs = self.remote_inputs.split_off(&oldest);
+        self.local_inputs = self.local_inputs.split_off(&self.confirmed.min(self.acknowledged));
+
+        for packet in packets {
+            self.read_packet(&packet);
+        }
+
+        let mut rollback = None;
+        while let Some(input) = self.remote_inputs.get(&self.confirmed).copied() {
+            if let Some(predicted) = self.predictions.remove(&self.confirmed) {
+                if predicted != input && rollback.is_none() {
+                    rollback = Some(self.confirmed);
+                }
+            }
+            self.confirmed += 1;
+        }
+
+        // A stalled peer sends nothing new, keep acknowledging so the other one can move on
+        if !self.can_advance() {
+            self.send_inputs();
+        }
+
+        rollback
+    }
+
+    /// Records the local input for the next frame and returns the inputs of every
+    /// player to simulate it with.
+    pub fn advance(&mut self, input: PlayerInput) -> [PlayerInput; 2] {
+        let frame = self.frame;
+        self.local_inputs.insert(frame, input);
+        self.frame += 1;
+        self.send_inputs();
+
+        self.inputs(frame)
+    }
+
+    /// Inputs of every player for a frame that was already advanced past, to
+    /// resimulate it after a rollback.
+    pub fn inputs(&mut self, frame: u32) -> [PlayerInput; 2] {
+        let local = self.local_inputs.get(&frame).copied().unwrap_or_default();
+        let remote = match self.remote_inputs.get(&frame) {
+            Some(input) => *input,
+            None => {
+                let predicted = self.predict();
+                self.predictions.insert(frame, predicted);
+                predicted
+            },
+        };
+
+        let mut inputs = [local; 2];
+        inputs[1 - self.local_player] = remote;
+
+        inputs
+    }
+
+    /// Buttons are most likely still held the way they were last heard of.
+    fn predict(&self) -> PlayerInput {
+        self.confirmed
+            .checked_sub(1)
+            .and_then(|frame| self.remote_inputs.get(&frame))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Every input the remote peer hasn't acknowledged yet goes out again, so a
+    /// lost packet is covered by the next one.
+    fn send_inputs(&mut self) {
+        let first = self.acknowledged;
+        let last = self.frame.min(first + MAX_INPUTS_PER_PACKET);
+
+        let mut packet = Vec::with_capacity(HEADER_SIZE + (last - first) as usize);
+        packet.extend_from_slice(&self.confirmed.to_le_bytes());
+        packet.extend_from_slice(&first.to_le_bytes());
+        packet.extend(self.local_inputs.range(first..last).map(|(_, input)| input.to_bits()));
+
+        if let Err(e) = self.transport.send(&packet) {
+            log::warn!("Failed to send inputs: {}", e);
+        }
+    }
+
+    /// Malformed packets are ignored like lost ones.
+    fn read_packet(&mut self, packet: &[u8]) {
+        if packet.len() < HEADER_SIZE {
+            return;
+        }
+
+        let acknowledged = u32::from_le_bytes(packet[0..4].try_into().unwrap());
+        let first = u32::from_le_bytes(packet[4..8].try_into().unwrap());
+        // Nothing past our own frame can have been received
+        self.acknowledged = self.acknowledged.max(acknowledged.min(self.frame));
+
+        for (i, bits) in packet[HEADER_SIZE..].iter().enumerate() {
+            let frame = first.saturating_add(i as u32);
+            if frame >= self.confirmed {
+                self.remote_inputs.entry(frame).or_insert_with(|| PlayerInput::from_bits(*bits));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::LoopbackTransport;
+
+    const LATENCY: usize = 2;
+
+    /// Each player presses something different every few frames.
+    fn scripted_input(player: usize, frame: u32) -> PlayerInput {
+        PlayerInput {
+            right: frame / 7 % 2 == player as u32,
+            jump: frame % 5 == player as u32,
+            dash: frame % 11 == 3,
+            ..Default::default()
+        }
+    }
+
+    fn sessions() -> [RollbackSession; 2] {
+        let (a, b) = LoopbackTransport::pair(LATENCY);
+
+        [RollbackSession::new(Box::new(a), 0), RollbackSession::new(Box::new(b), 1)]
+    }
+
+    /// Runs a frame of the game loop on one side, returns where `poll` rolled back to
+    /// and the inputs of every frame it confirmed.
+    fn tick(session: &mut RollbackSession, input: impl Fn(u32) -> PlayerInput) -> (Option<u32>, Vec<[PlayerInput; 2]>) {
+        let confirmed = session.confirmed_frame();
+        let rollback = session.poll();
+
+        if let Some(frame) = rollback {
+            for frame in frame..session.frame() {
+                session.inputs(frame);
+            }
+        }
+        let inputs = (confirmed..session.confirmed_frame())
+            .map(|frame| session.inputs(frame))
+            .collect();
+
+        if session.can_advance() {
+            let frame = session.frame();
+            session.advance(input(frame));
+        }
+
+        (rollback, inputs)
+    }
+
+    #[test]
+    fn poll_returns_the_first_mispredicted_frame() {
+        let mut sessions = sessions();
+        let mut rollbacks = Vec::new();
+
+        for _ in 0..12 {
+            rollbacks.extend(tick(&mut sessions[0], |_| PlayerInput::default()).0);
+            // Jumps from frame 3 on, after the first session predicted frames past it
+            tick(&mut sessions[1], |frame| PlayerInput {
+                jump: frame >= 3,
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(rollbacks, vec!{3});
+    }
+
+    #[test]
+    fn both_sides_agree_on_confirmed_inputs() {
+        let mut sessions = sessions();
+        let mut confirmed = [Vec::new(), Vec::new()];
+
+        for _ in 0..200 {
+            for (player, session) in sessions.iter_mut().enumerate() {
+                let (_, inputs) = tick(session, |frame| scripted_input(player, frame));
+                confirmed[player].extend(inputs);
+            }
+        }
+
+        let frames = confirmed[0].len().min(confirmed[1].len());
+        assert!(frames > 150);
+        assert_eq!(confirmed[0][..frames], confirmed[1][..frames]);
+        for (frame, inputs) in confirmed[0][..frames].iter().enumerate() {
+            let frame = frame as u32;
+            assert_eq!(*inputs, [scripted_input(0, frame), scripted_input(1, frame)]);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use super::Transport;
+
+use std::{io, net::{SocketAddr, UdpSocket}};
+
+/// Largest packet `receive` accepts, well above anything the rollback session sends.
+const MAX_PACKET_SIZE: usize = 1024;
+
+pub struct UdpTransport {
+    socket: UdpSocket,
+    peer: SocketAddr,
+}
+
+impl UdpTransport {
+    /// Listens on `local` and only talks to `peer`.
+    pub fn bind(local: SocketAddr, peer: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            peer,
+        })
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send_to(packet, self.peer)?;
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let mut packets = Vec::new();
+        let mut buffer = [0; MAX_PACKET_SIZE];
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, from)) if from == self.peer => packets.push(buffer[..size].to_vec()),
+                Ok(_) => (),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(packets),
+                // Windows reports the peer's closed port on the next receive, it may still come up
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+use crate::{graphics, Context, util};
+use nalgebra::Point2;
+
+/// Seconds a notice stays on screen.
+const NOTICE_DURATION: f32 = 4.;
+/// Older notices are dropped to make room for new ones.
+const MAX_NOTICES: usize = 4;
+const LINE_HEIGHT: f32 = 20.;
+
+struct Notice {
+    text: String,
+    remaining: f32,
+}
+
+/// Short messages for the player, drawn on top of whatever scene is running.
+#[derive(Default)]
+pub struct Notices {
+    notices: VecDeque<Notice>,
+}
+
+impl Notices {
+    pub fn show<S: Into<String>>(&mut self, text: S) {
+        if self.notices.len() == MAX_NOTICES {
+            self.notices.pop_front();
+        }
+
+        self.notices.push_back(Notice {
+            text: text.into(),
+            remaining: NOTICE_DURATION,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for notice in self.notices.iter_mut() {
+            notice.remaining -= dt;
+        }
+        self.notices.retain(|notice| notice.remaining > 0.);
+    }
+
+    /// Stacked up from the bottom left corner, in screen coordinates.
+    pub fn draw(&self, ctx: &mut Context) {
+        let bottom = graphics::drawable_size(ctx).1 - 10.;
+
+        for (row, notice) in self.notices.iter().rev().enumerate() {
+            let fragment = graphics::TextFragment::new(notice.text.as_str())
+                .color(graphics::Color::from((255, 220, 160, 255)))
+                .scale(graphics::Scale::uniform(18.0));
+
+            graphics::draw(
+                ctx,
+                &graphics::Text::new(fragment),
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(10., bottom - (row + 1) as f32 * LINE_HEIGHT))),
+            )
+            .unwrap();
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,949 @@
+use crate::entity::Entity;
+use super::*;
+
+use std::{cmp::Ordering, collections::{BTreeMap, BTreeSet}};
+
+use ncollide2d::bounding_volume::{BoundingVolume, AABB};
+
+use nalgebra::{Point2, Vector2};
+
+/// How close two boxes have to be, in pixels, to count as touching.
+const CONTACT_SKIN: f32 = 0.01;
+/// Furthest a body moves, in pixels, before it is pushed back out of rotated colliders.
+/// Keeps it from passing through thin ramps.
+const MAX_SUBSTEP: f32 = 4.;
+
+struct Body {
+    kind: BodyKind,
+    position: Point2<f32>,
+    velocity: Vector2<f32>,
+    gravity_enabled: bool,
+}
+
+struct Collider {
+    body: BodyHandle,
+    shape: ColliderShape,
+    translation: Vector2<f32>,
+    /// In radians, bodies don't rotate but their colliders can be placed rotated.
+    rotation: f32,
+    /// Of the bounding box.
+    half_extents: Vector2<f32>,
+    sensor: bool,
+    entity: Entity,
+}
+
+impl Collider {
+    fn is_rotated(&self) -> bool {
+        self.rotation != 0.
+    }
+}
+
+/// A box turned by some angle, what rotated colliders are tested as.
+#[derive(Debug, Clone, Copy)]
+struct OrientedBox {
+    center: Point2<f32>,
+    half_extents: Vector2<f32>,
+    /// The box's own x and y axes.
+    axes: [Vector2<f32>; 2],
+}
+
+impl OrientedBox {
+    fn new(center: Point2<f32>, half_extents: Vector2<f32>, rotation: f32) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+
+        Self {
+            center,
+            half_extents,
+            axes: [Vector2::new(cos, sin), Vector2::new(-sin, cos)],
+        }
+    }
+
+    /// Half the length of the box projected onto `axis`.
+    fn radius_along(&self, axis: &Vector2<f32>) -> f32 {
+        self.half_extents.x * self.axes[0].dot(axis).abs() + self.half_extents.y * self.axes[1].dot(axis).abs()
+    }
+
+    /// Both boxes' axes, the only ones that can separate two boxes.
+    fn separating_axes(&self, other: &OrientedBox) -> [Vector2<f32>; 4] {
+        [self.axes[0], self.axes[1], other.axes[0], other.axes[1]]
+    }
+
+    /// Gap between the boxes along the axis they are furthest apart on, negative while
+    /// they overlap, and that axis pointing from `self` to `other`.
+    fn separation(&self, other: &OrientedBox) -> (f32, Vector2<f32>) {
+        let delta = other.center - self.center;
+
+        self.separating_axes(other)
+            .iter()
+            .map(|axis| {
+                let distance = delta.dot(axis);
+                let gap = distance.abs() - self.radius_along(axis) - other.radius_along(axis);
+                let normal = if distance < 0. { -*axis } else { *axis };
+
+                (gap, normal)
+            })
+            .fold((f32::NEG_INFINITY, Vector2::zeros()), |furthest, axis| {
+                if axis.0 > furthest.0 { axis } else { furthest }
+            })
+    }
+
+    /// Distance `self` moves along `dir` before it touches `other`, with the normal of the
+    /// face it touches. Zero when they already overlap.
+    fn sweep(&self, dir: Vector2<f32>, other: &OrientedBox) -> Option<(f32, Vector2<f32>)> {
+        let delta = other.center - self.center;
+        let mut entry = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        let mut normal = -dir;
+
+        for axis in self.separating_axes(other).iter() {
+            let distance = delta.dot(axis);
+            let radius = self.radius_along(axis) + other.radius_along(axis);
+            let speed = dir.dot(axis);
+
+            if speed == 0. {
+                if distance.abs() > radius {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (distance - radius) / speed;
+            let t2 = (distance + radius) / speed;
+            let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+            if near > entry {
+                entry = near;
+                normal = -*axis * speed.signum();
+            }
+            exit = exit.min(far);
+        }
+
+        if entry > exit || exit < 0. {
+            None
+        } else if entry < 0. {
+            Some((0., -dir))
+        } else {
+            Some((entry, normal))
+        }
+    }
+
+    fn corners(&self) -> Vec<Point2<f32>> {
+        let x = self.axes[0] * self.half_extents.x;
+        let y = self.axes[1] * self.half_extents.y;
+
+        vec!{
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        }
+    }
+}
+
+struct Rope {
+    hook: ColliderHandle,
+    hooked: BodyHandle,
+    /// Offset of the hook from the hooked body's position.
+    anchor: Vector2<f32>,
+    attached: BodyHandle,
+    length: f32,
+}
+
+/// A small platformer solver working directly in pixels. Bodies never rotate,
+/// dynamic ones move one axis at a time and stop flush against the axis aligned
+/// boxes they hit. Rotated colliders, like ramps, push bodies back out along their
+/// surface instead, so bodies slide along them. Everything is stored in ordered
+/// maps and stepped with a fixed time step, so the same inputs always give the
+/// same result.
+pub struct AabbBackend {
+    bodies: BTreeMap<BodyHandle, Body>,
+    colliders: BTreeMap<ColliderHandle, Collider>,
+    next_handle: usize,
+
+    gravity: Vector2<f32>,
+    max_slope: f32,
+    gravity_zones: GravityZones,
+    force_zones: Vec<(ForceZoneHandle, ForceZone)>,
+    ropes: Vec<(RopeHandle, Rope)>,
+    removal_queue: RemovalQueue,
+    collision_rules: CollisionRules,
+
+    /// Touching solid pairs with the normal from the first collider to the second.
+    contacts: BTreeMap<(ColliderHandle, ColliderHandle), Vector2<f32>>,
+    /// Overlapping pairs where at least one is a sensor.
+    proximities: BTreeSet<(ColliderHandle, ColliderHandle)>,
+    events: Vec<PhysicsEvent>,
+    /// Seconds simulated so far, drives the gusts of force zones.
+    time: f32,
+    ticks: usize,
+}
+
+impl AabbBackend {
+    pub fn new() -> Self {
+        Self {
+            bodies: BTreeMap::new(),
+            colliders: BTreeMap::new(),
+            next_handle: 0,
+            gravity: Vector2::new(0., DEFAULT_GRAVITY),
+            max_slope: DEFAULT_MAX_SLOPE,
+            gravity_zones: GravityZones::default(),
+            force_zones: Vec::new(),
+            ropes: Vec::new(),
+            removal_queue: RemovalQueue::default(),
+            collision_rules: CollisionRules::default(),
+            contacts: BTreeMap::new(),
+            proximities: BTreeSet::new(),
+            events: Vec::new(),
+            time: 0.,
+            ticks: 0,
+        }
+    }
+
+    fn next_handle(&mut self) -> usize {
+        self.next_handle += 1;
+        self.next_handle
+    }
+
+    fn aabb(&self, collider: &Collider) -> Option<AABB<f32>> {
+        let center = self.bodies.get(&collider.body)?.position + collider.translation;
+
+        Some(AABB::new(center - collider.half_extents, center + collider.half_extents))
+    }
+
+    fn oriented_box(&self, collider: &Collider) -> Option<OrientedBox> {
+        let center = self.bodies.get(&collider.body)?.position + collider.translation;
+
+        Some(OrientedBox::new(center, collider.shape.half_extents(0.), collider.rotation))
+    }
+
+    /// Whether two colliders can touch at all, bodies that can't be pushed never
+    /// collide with each other.
+    fn interacts(&self, a: &Collider, b: &Collider) -> bool {
+        if a.body == b.body || !self.collision_rules.interacts(a.entity.kind, b.entity.kind) {
+            return false;
+        }
+
+        let is_dynamic = |collider: &Collider| {
+            self.bodies
+                .get(&collider.body)
+                .map(|body| body.kind == BodyKind::Dynamic)
+                .unwrap_or(false)
+        };
+
+        is_dynamic(a) || is_dynamic(b)
+    }
+
+    fn integrate_velocities(&mut self, dt: f32) {
+        let handles: Vec<BodyHandle> = self.bodies.keys().copied().collect();
+        for handle in handles {
+            let acceleration = match self.bodies.get(&handle) {
+                Some(body) if body.kind == BodyKind::Dynamic && body.gravity_enabled => {
+                    self.gravity_at(body.position) + self.force_at(body.position)
+                },
+                _ => continue,
+            };
+
+            if let Some(body) = self.bodies.get_mut(&handle) {
+                body.velocity += acceleration * dt;
+            }
+        }
+    }
+
+    /// Moves a dynamic body by `amount` along one axis, stopping at the first solid
+    /// axis aligned collider in the way. Returns the distance actually moved.
+    fn move_axis(&mut self, handle: BodyHandle, axis: usize, amount: f32) -> f32 {
+        if amount == 0. {
+            return 0.;
+        }
+        let other_axis = 1 - axis;
+        let mut allowed = amount;
+
+        for collider in self.colliders.values().filter(|collider| collider.body == handle && !collider.sensor) {
+            let a = match self.aabb(collider) {
+                Some(aabb) => aabb,
+                None => continue,
+            };
+
+            let solid = self.colliders
+                .values()
+                .filter(|other| !other.sensor && self.interacts(collider, other))
+                .filter(|other| !collider.is_rotated() && !other.is_rotated());
+
+            for other in solid {
+                let b = match self.aabb(other) {
+                    Some(aabb) => aabb,
+                    None => continue,
+                };
+
+                // Sliding along a surface is not running into it
+                if a.mins()[other_axis] >= b.maxs()[other_axis] || a.maxs()[other_axis] <= b.mins()[other_axis] {
+                    continue;
+                }
+
+                // Already overlapping boxes let go of each other instead of getting stuck
+                if amount > 0. && a.maxs()[axis] <= b.mins()[axis] + CONTACT_SKIN {
+                    allowed = allowed.min((b.mins()[axis] - a.maxs()[axis]).max(0.));
+                } else if amount < 0. && a.mins()[axis] >= b.maxs()[axis] - CONTACT_SKIN {
+                    allowed = allowed.max((b.maxs()[axis] - a.mins()[axis]).min(0.));
+                }
+            }
+        }
+
+        if let Some(body) = self.bodies.get_mut(&handle) {
+            body.position[axis] += allowed;
+            if allowed != amount {
+                body.velocity[axis] = 0.;
+            }
+        }
+
+        allowed
+    }
+
+    /// Moves out of every rotated collider a dynamic body overlaps, along the axis it
+    /// overlaps least on, and stops the body moving further into them.
+    fn push_out_of_rotated(&mut self, handle: BodyHandle) {
+        let own: Vec<ColliderHandle> = self.colliders
+            .iter()
+            .filter(|(_, collider)| collider.body == handle && !collider.sensor)
+            .map(|(collider_handle, _)| *collider_handle)
+            .collect();
+
+        for collider_handle in own {
+            let others: Vec<OrientedBox> = match self.colliders.get(&collider_handle) {
+                Some(collider) => self.colliders
+                    .values()
+                    .filter(|other| !other.sensor && self.interacts(collider, other))
+                    .filter(|other| collider.is_rotated() || other.is_rotated())
+                    .filter_map(|other| self.oriented_box(other))
+                    .collect(),
+                None => continue,
+            };
+
+            for other in others {
+                let own_box = match self.colliders.get(&collider_handle).and_then(|collider| self.oriented_box(collider)) {
+                    Some(own_box) => own_box,
+                    None => continue,
+                };
+
+                let (gap, normal) = own_box.separation(&other);
+                if gap >= 0. {
+                    continue;
+                }
+
+                if let Some(body) = self.bodies.get_mut(&handle) {
+                    body.position += normal * gap;
+
+                    let inwards = body.velocity.dot(&normal);
+                    if inwards > 0. {
+                        body.velocity -= normal * inwards;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves a dynamic body by `delta`, in small enough steps to get pushed out of
+    /// rotated colliders before passing through them.
+    fn move_body(&mut self, handle: BodyHandle, delta: Vector2<f32>) {
+        let has_rotated = self.colliders.values().any(|collider| collider.is_rotated() && !collider.sensor);
+        let steps = if has_rotated {
+            (delta.norm() / MAX_SUBSTEP).ceil().max(1.) as usize
+        } else {
+            1
+        };
+        let step = delta / steps as f32;
+
+        for _ in 0..steps {
+            self.move_axis(handle, 0, step.x);
+            self.move_axis(handle, 1, step.y);
+            if has_rotated {
+                self.push_out_of_rotated(handle);
+            }
+        }
+    }
+
+    fn move_bodies(&mut self, dt: f32) {
+        let handles: Vec<BodyHandle> = self.bodies.keys().copied().collect();
+        for handle in handles {
+            let (kind, velocity) = match self.bodies.get(&handle) {
+                Some(body) => (body.kind, body.velocity),
+                None => continue,
+            };
+
+            match kind {
+                BodyKind::Dynamic => self.move_body(handle, velocity * dt),
+                BodyKind::Kinematic => {
+                    if let Some(body) = self.bodies.get_mut(&handle) {
+                        body.position += velocity * dt;
+                    }
+                },
+                BodyKind::Static => (),
+            }
+        }
+    }
+
+    /// Pulls every attached body back inside its rope's length and drops the
+    /// part of its velocity that points away from the hook.
+    fn apply_ropes(&mut self) {
+        let ropes: Vec<(Point2<f32>, BodyHandle, f32)> = self.ropes
+            .iter()
+            .filter_map(|(_, rope)| {
+                let hook = self.bodies.get(&rope.hooked)?.position + rope.anchor;
+                Some((hook, rope.attached, rope.length))
+            })
+            .collect();
+
+        for (hook, attached, length) in ropes {
+            let position = match self.bodies.get(&attached) {
+                Some(body) => body.position,
+                None => continue,
+            };
+
+            let offset = position - hook;
+            let distance = offset.norm();
+            if distance <= length || distance <= f32::EPSILON {
+                continue;
+            }
+
+            let direction = offset / distance;
+            self.move_body(attached, -direction * (distance - length));
+
+            if let Some(body) = self.bodies.get_mut(&attached) {
+                let outwards = body.velocity.dot(&direction).max(0.);
+                body.velocity -= direction * outwards;
+            }
+        }
+    }
+
+    /// Normal from `a` to `b` if the boxes touch. Corners that only meet at a point
+    /// don't count, overlapping boxes get the axis they overlap least on.
+    fn contact_normal(a: &AABB<f32>, b: &AABB<f32>) -> Option<Vector2<f32>> {
+        let gap_x = (a.mins().x - b.maxs().x).max(b.mins().x - a.maxs().x);
+        let gap_y = (a.mins().y - b.maxs().y).max(b.mins().y - a.maxs().y);
+        if gap_x > CONTACT_SKIN || gap_y > CONTACT_SKIN {
+            return None;
+        }
+
+        let overlap_x = gap_x < -CONTACT_SKIN;
+        let overlap_y = gap_y < -CONTACT_SKIN;
+        let horizontal = match (overlap_x, overlap_y) {
+            (false, true) => true,
+            (true, false) => false,
+            (true, true) => gap_x > gap_y,
+            (false, false) => return None,
+        };
+
+        let delta = b.center() - a.center();
+        if horizontal {
+            Some(Vector2::new(delta.x.signum(), 0.))
+        } else {
+            Some(Vector2::new(0., delta.y.signum()))
+        }
+    }
+
+    fn update_contacts(&mut self) {
+        let mut contacts = BTreeMap::new();
+        let mut proximities = BTreeSet::new();
+
+        let colliders: Vec<(ColliderHandle, &Collider, AABB<f32>, OrientedBox)> = self.colliders
+            .iter()
+            .filter_map(|(handle, collider)| Some((*handle, collider, self.aabb(collider)?, self.oriented_box(collider)?)))
+            .collect();
+
+        for (i, (handle1, collider1, aabb1, box1)) in colliders.iter().enumerate() {
+            for (handle2, collider2, aabb2, box2) in &colliders[i + 1..] {
+                if !self.interacts(collider1, collider2) {
+                    continue;
+                }
+
+                let is_rotated = collider1.is_rotated() || collider2.is_rotated();
+                if collider1.sensor || collider2.sensor {
+                    let overlaps = if is_rotated {
+                        box1.separation(box2).0 <= 0.
+                    } else {
+                        aabb1.intersects(aabb2)
+                    };
+
+                    if overlaps {
+                        proximities.insert((*handle1, *handle2));
+                    }
+                } else if is_rotated {
+                    let (gap, normal) = box1.separation(box2);
+                    if gap <= CONTACT_SKIN {
+                        contacts.insert((*handle1, *handle2), normal);
+                    }
+                } else if let Some(normal) = Self::contact_normal(aabb1, aabb2) {
+                    contacts.insert((*handle1, *handle2), normal);
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        let started = proximities.difference(&self.proximities).map(|pair| (PhysicsEventKind::ProximityStarted, *pair));
+        let stopped = self.proximities.difference(&proximities).map(|pair| (PhysicsEventKind::ProximityStopped, *pair));
+        events.extend(started.chain(stopped).filter_map(|(kind, pair)| self.new_event(kind, pair)));
+
+        let started = contacts.keys().filter(|pair| !self.contacts.contains_key(pair)).map(|pair| (PhysicsEventKind::ContactStarted, *pair));
+        let stopped = self.contacts.keys().filter(|pair| !contacts.contains_key(pair)).map(|pair| (PhysicsEventKind::ContactStopped, *pair));
+        events.extend(started.chain(stopped).filter_map(|(kind, pair)| self.new_event(kind, pair)));
+
+        self.contacts = contacts;
+        self.proximities = proximities;
+        self.events = events;
+    }
+
+    fn new_event(&self, kind: PhysicsEventKind, colliders: (ColliderHandle, ColliderHandle)) -> Option<PhysicsEvent> {
+        // Pairs that stopped because a collider was removed have no entity left
+        Some(PhysicsEvent {
+            kind,
+            colliders,
+            entities: (self.entity(colliders.0)?, self.entity(colliders.1)?),
+        })
+    }
+
+    /// Distance along `dir` at which a ray from `origin` enters `aabb`, with the
+    /// normal of the face it enters through. Zero when it starts inside.
+    fn ray_aabb(origin: Point2<f32>, dir: Vector2<f32>, aabb: &AABB<f32>) -> Option<(f32, Vector2<f32>)> {
+        let mut entry = 0.;
+        let mut exit = f32::INFINITY;
+        let mut normal = -dir;
+
+        for axis in 0..2 {
+            if dir[axis] == 0. {
+                if origin[axis] < aabb.mins()[axis] || origin[axis] > aabb.maxs()[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (aabb.mins()[axis] - origin[axis]) / dir[axis];
+            let t2 = (aabb.maxs()[axis] - origin[axis]) / dir[axis];
+            let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+            if near > entry {
+                entry = near;
+                normal = Vector2::zeros();
+                normal[axis] = -dir[axis].signum();
+            }
+            exit = exit.min(far);
+        }
+
+        if entry <= exit {
+            Some((entry, normal))
+        } else {
+            None
+        }
+    }
+
+    /// Closest hit of an axis aligned box with `half_extents` swept from `origin`, as the
+    /// distance, the normal and the collider. A zero sized box makes it a raycast.
+    fn sweep(
+        &self,
+        half_extents: Vector2<f32>,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<(f32, Vector2<f32>, ColliderHandle, &Collider)> {
+        self.colliders
+            .iter()
+            .filter(|(handle, collider)| filter.accepts(**handle, collider.entity, collider.sensor))
+            .filter_map(|(handle, collider)| {
+                let (distance, normal) = if collider.is_rotated() {
+                    OrientedBox::new(origin, half_extents, 0.).sweep(dir, &self.oriented_box(collider)?)?
+                } else {
+                    let aabb = self.aabb(collider)?;
+                    let expanded = AABB::new(aabb.mins() - half_extents, aabb.maxs() + half_extents);
+                    Self::ray_aabb(origin, dir, &expanded)?
+                };
+
+                if distance <= max_dist {
+                    Some((distance, normal, *handle, collider))
+                } else {
+                    None
+                }
+            })
+            .min_by(|(a, _, _, _), (b, _, _, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+}
+
+impl PhysicsBackend for AabbBackend {
+    fn step(&mut self) {
+        self.flush_removals();
+
+        let dt = TIME_STEP;
+        self.time += dt;
+
+        self.integrate_velocities(dt);
+        self.move_bodies(dt);
+        self.apply_ropes();
+        self.update_contacts();
+
+        self.ticks += 1;
+    }
+
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn set_clock(&mut self, ticks: usize, time: f32) {
+        self.ticks = ticks;
+        self.time = time;
+    }
+
+    fn events(&self) -> &[PhysicsEvent] {
+        &self.events
+    }
+
+    fn get_gravity(&self) -> Vector2<f32> {
+        self.gravity
+    }
+
+    fn set_gravity(&mut self, gravity: Vector2<f32>) {
+        self.gravity = gravity;
+    }
+
+    fn get_max_slope(&self) -> f32 {
+        self.max_slope
+    }
+
+    fn set_max_slope(&mut self, max_slope: f32) {
+        self.max_slope = max_slope;
+    }
+
+    fn add_gravity_zone(&mut self, zone: GravityZone) -> GravityZoneHandle {
+        self.gravity_zones.add(zone)
+    }
+
+    fn remove_gravity_zone(&mut self, handle: GravityZoneHandle) -> bool {
+        self.gravity_zones.remove(handle)
+    }
+
+    fn gravity_zones(&self) -> Vec<GravityZone> {
+        self.gravity_zones.to_vec()
+    }
+
+    fn gravity_at(&self, point: Point2<f32>) -> Vector2<f32> {
+        self.gravity_zones.gravity_at(point, self.gravity)
+    }
+
+    fn add_force_zone(&mut self, zone: ForceZone) -> ForceZoneHandle {
+        let handle = ForceZoneHandle(self.next_handle());
+        self.force_zones.push((handle, zone));
+
+        handle
+    }
+
+    fn remove_force_zone(&mut self, handle: ForceZoneHandle) -> bool {
+        let count = self.force_zones.len();
+        self.force_zones.retain(|(zone_handle, _)| *zone_handle != handle);
+
+        self.force_zones.len() != count
+    }
+
+    fn force_zones(&self) -> Vec<(ForceZone, bool)> {
+        self.force_zones
+            .iter()
+            .map(|(_, zone)| (zone.clone(), zone.is_blowing(self.time)))
+            .collect()
+    }
+
+    fn force_at(&self, point: Point2<f32>) -> Vector2<f32> {
+        self.force_zones
+            .iter()
+            .filter(|(_, zone)| zone.is_blowing(self.time) && zone.area.contains_local_point(&point))
+            .fold(Vector2::zeros(), |sum, (_, zone)| sum + zone.acceleration)
+    }
+
+    /// Bodies have no mass here, nothing pushes a dynamic body but gravity, forces and ropes.
+    fn add_body(&mut self, desc: BodyDesc) -> BodyHandle {
+        let handle = BodyHandle(self.next_handle());
+        self.bodies.insert(handle, Body {
+            kind: desc.kind,
+            position: desc.position,
+            velocity: Vector2::zeros(),
+            gravity_enabled: desc.gravity_enabled,
+        });
+
+        handle
+    }
+
+    fn remove_body(&mut self, handle: BodyHandle) -> bool {
+        self.ropes.retain(|(_, rope)| rope.hooked != handle && rope.attached != handle);
+        self.colliders.retain(|_, collider| collider.body != handle);
+
+        self.bodies.remove(&handle).is_some()
+    }
+
+    fn get_position(&self, handle: BodyHandle) -> Option<Point2<f32>> {
+        self.bodies.get(&handle).map(|body| body.position)
+    }
+
+    fn set_position(&mut self, handle: BodyHandle, position: Point2<f32>) {
+        if let Some(body) = self.bodies.get_mut(&handle) {
+            body.position = position;
+        }
+    }
+
+    fn get_velocity(&self, handle: BodyHandle) -> Option<Point2<f32>> {
+        self.bodies.get(&handle).map(|body| Point2::from(body.velocity))
+    }
+
+    fn set_velocity(&mut self, handle: BodyHandle, velocity: Point2<f32>) {
+        if let Some(body) = self.bodies.get_mut(&handle) {
+            body.velocity = velocity.coords;
+        }
+    }
+
+    fn set_gravity_enabled(&mut self, handle: BodyHandle, enabled: bool) {
+        if let Some(body) = self.bodies.get_mut(&handle) {
+            body.gravity_enabled = enabled;
+        }
+    }
+
+    fn bodies(&self) -> Vec<(BodyHandle, BodyKind)> {
+        self.bodies
+            .iter()
+            .map(|(handle, body)| (*handle, body.kind))
+            .collect()
+    }
+
+    fn add_collider(&mut self, body: BodyHandle, desc: ColliderDesc, entity: Entity) -> Option<ColliderHandle> {
+        if !self.bodies.contains_key(&body) {
+            return None;
+        }
+
+        let handle = ColliderHandle(self.next_handle());
+        self.colliders.insert(handle, Collider {
+            body,
+            shape: desc.shape,
+            translation: desc.translation,
+            rotation: desc.rotation,
+            half_extents: desc.shape.half_extents(desc.rotation),
+            sensor: desc.sensor,
+            entity,
+        });
+
+        Some(handle)
+    }
+
+    fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
+        self.colliders.remove(&handle).is_some()
+    }
+
+    fn entity(&self, handle: ColliderHandle) -> Option<Entity> {
+        self.colliders.get(&handle).map(|collider| collider.entity)
+    }
+
+    fn collider_position(&self, handle: ColliderHandle) -> Option<Point2<f32>> {
+        let collider = self.colliders.get(&handle)?;
+
+        Some(self.bodies.get(&collider.body)?.position + collider.translation)
+    }
+
+    fn collider_outlines(&self) -> Vec<Vec<Point2<f32>>> {
+        self.colliders
+            .values()
+            .filter_map(|collider| self.oriented_box(collider))
+            .map(|oriented_box| oriented_box.corners())
+            .collect()
+    }
+
+    fn collision_rules_mut(&mut self) -> &mut CollisionRules {
+        &mut self.collision_rules
+    }
+
+    fn removal_queue(&self) -> RemovalQueue {
+        self.removal_queue.clone()
+    }
+
+    fn contact_normals(&self, handle: ColliderHandle) -> Vec<(Entity, Vector2<f32>)> {
+        self.contacts
+            .iter()
+            .filter_map(|((handle1, handle2), normal)| {
+                if *handle1 == handle {
+                    Some((self.entity(*handle2)?, *normal))
+                } else if *handle2 == handle {
+                    Some((self.entity(*handle1)?, -*normal))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn raycast(
+        &self,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit> {
+        let dir = dir.try_normalize(f32::EPSILON)?;
+        let (distance, normal, handle, collider) = self.sweep(Vector2::zeros(), origin, dir, max_dist, filter)?;
+
+        Some(QueryHit {
+            collider: handle,
+            entity: collider.entity,
+            point: origin + dir * distance,
+            normal,
+            distance,
+        })
+    }
+
+    fn shape_cast(
+        &self,
+        shape: &ColliderShape,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit> {
+        let dir = dir.try_normalize(f32::EPSILON).unwrap_or_else(Vector2::zeros);
+        let half_extents = shape.half_extents(0.);
+        let (distance, normal, handle, collider) = self.sweep(half_extents, origin, dir, max_dist, filter)?;
+
+        // The shape touches the collider with the face opposite to the normal
+        let center = origin + dir * distance;
+        Some(QueryHit {
+            collider: handle,
+            entity: collider.entity,
+            point: center - normal.component_mul(&half_extents),
+            normal,
+            distance,
+        })
+    }
+
+    fn attach_rope(&mut self, body: BodyHandle, hook: ColliderHandle, point: Point2<f32>) -> Option<RopeHandle> {
+        let hooked = self.colliders.get(&hook)?.body;
+        let anchor = point - self.bodies.get(&hooked)?.position;
+        let length = (self.bodies.get(&body)?.position - point).norm();
+        if length <= f32::EPSILON {
+            return None;
+        }
+
+        let handle = RopeHandle(self.next_handle());
+        self.ropes.push((handle, Rope {
+            hook,
+            hooked,
+            anchor,
+            attached: body,
+            length,
+        }));
+
+        Some(handle)
+    }
+
+    fn detach_rope(&mut self, handle: RopeHandle) -> bool {
+        let count = self.ropes.len();
+        self.ropes.retain(|(rope_handle, _)| *rope_handle != handle);
+
+        self.ropes.len() != count
+    }
+
+    fn rope_length(&self, handle: RopeHandle) -> Option<f32> {
+        self.ropes
+            .iter()
+            .find(|(rope_handle, _)| *rope_handle == handle)
+            .map(|(_, rope)| rope.length)
+    }
+
+    fn set_rope_length(&mut self, handle: RopeHandle, length: f32) {
+        if let Some((_, rope)) = self.ropes.iter_mut().find(|(rope_handle, _)| *rope_handle == handle) {
+            rope.length = length;
+        }
+    }
+
+    fn rope_hook(&self, handle: RopeHandle) -> Option<(ColliderHandle, Point2<f32>)> {
+        let (_, rope) = self.ropes.iter().find(|(rope_handle, _)| *rope_handle == handle)?;
+
+        Some((rope.hook, self.bodies.get(&rope.hooked)?.position + rope.anchor))
+    }
+
+    fn ropes(&self) -> Vec<(Point2<f32>, Point2<f32>)> {
+        self.ropes
+            .iter()
+            .filter_map(|(_, rope)| {
+                let hook = self.bodies.get(&rope.hooked)?.position + rope.anchor;
+                Some((hook, self.bodies.get(&rope.attached)?.position))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityAllocator;
+
+    /// Y points down, so the ramp goes down to the right.
+    const RAMP_ROTATION: f32 = 0.5;
+
+    /// Where a vertical line at `x` meets the top of the ramp.
+    fn ramp_surface(x: f32) -> f32 {
+        x * RAMP_ROTATION.tan() - 1. / RAMP_ROTATION.cos()
+    }
+
+    /// A ramp centered on the origin and a box dropped onto it from above.
+    fn ramp_and_box() -> (AabbBackend, ColliderHandle, BodyHandle, ColliderHandle) {
+        let mut entities = EntityAllocator::default();
+        let mut physics = AabbBackend::new();
+        physics.set_gravity(Vector2::new(0., 600.));
+
+        let ramp_body = physics.add_body(BodyDesc::fixed());
+        let ramp = physics.add_collider(
+            ramp_body,
+            ColliderDesc::new(ColliderShape::Cuboid(Vector2::new(40., 1.))).rotation(RAMP_ROTATION),
+            entities.spawn(ObjectType::Platform),
+        ).unwrap();
+
+        let body = physics.add_body(BodyDesc::dynamic().position(Point2::new(0., -40.)));
+        let collider = physics.add_collider(
+            body,
+            ColliderDesc::new(ColliderShape::Cuboid(Vector2::repeat(5.))),
+            entities.spawn(ObjectType::Player),
+        ).unwrap();
+
+        (physics, ramp, body, collider)
+    }
+
+    #[test]
+    fn boxes_land_on_rotated_colliders() {
+        let (mut physics, ramp, body, collider) = ramp_and_box();
+        for _ in 0..30 {
+            physics.step();
+        }
+
+        let position = physics.get_position(body).unwrap();
+        let ramp_box = physics.oriented_box(&physics.colliders[&ramp]).unwrap();
+        let own_box = physics.oriented_box(&physics.colliders[&collider]).unwrap();
+        let (gap, _) = own_box.separation(&ramp_box);
+        assert!(position.y < ramp_surface(position.x), "fell through the ramp to {}", position);
+        assert!(gap.abs() <= CONTACT_SKIN, "{} away from the ramp", gap);
+
+        let normal = Vector2::new(-RAMP_ROTATION.sin(), RAMP_ROTATION.cos());
+        let contacts = physics.contact_normals(collider);
+        assert_eq!(contacts.len(), 1);
+        assert!((contacts[0].1 - normal).norm() < 1e-4, "contact normal {}", contacts[0].1);
+        assert!(physics.ground_check(collider, ObjectTypeSet::of(&[ObjectType::Platform])));
+    }
+
+    #[test]
+    fn rays_hit_the_surface_of_rotated_colliders() {
+        let (physics, ramp, _, _) = ramp_and_box();
+        let filter = QueryFilter::default();
+
+        let hit = physics.raycast(Point2::new(20., -20.), Vector2::new(0., 1.), 100., &filter).unwrap();
+        let surface = ramp_surface(20.);
+        assert_eq!(hit.collider, ramp);
+        assert!((hit.point.y - surface).abs() < 1e-3, "hit at {}, surface at {}", hit.point, surface);
+        assert!((hit.normal - Vector2::new(RAMP_ROTATION.sin(), -RAMP_ROTATION.cos())).norm() < 1e-4);
+
+        // Inside the ramp's bounding box, but well below the ramp
+        assert!(physics.raycast(Point2::new(-30., 15.), Vector2::new(0., 1.), 2., &filter).is_none());
+    }
+}
\ No newline at end of file
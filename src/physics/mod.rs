@@ -0,0 +1,720 @@
+mod nphysics;
+mod aabb;
+
+pub use self::nphysics::NPhysicsBackend;
+pub use self::aabb::AabbBackend;
+
+use crate::entity::Entity;
+
+use std::{cell::RefCell, rc::Rc, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use ncollide2d::bounding_volume::AABB;
+
+use nalgebra::{Point2, Vector2};
+
+/// Seconds simulated by every `step`.
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+const DEFAULT_GRAVITY: f32 = 30.;
+/// Steepest surface, in radians, that still counts as floor or ceiling.
+const DEFAULT_MAX_SLOPE: f32 = std::f32::consts::FRAC_PI_4;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum ObjectType {
+    Player,
+    Enemy,
+    Platform,
+    Lava,
+    Pickup,
+    Checkpoint,
+}
+
+impl ObjectType {
+    pub const ALL: [ObjectType; 6] = [
+        ObjectType::Player,
+        ObjectType::Enemy,
+        ObjectType::Platform,
+        ObjectType::Lava,
+        ObjectType::Pickup,
+        ObjectType::Checkpoint,
+    ];
+
+    /// Every object type is its own collision group.
+    fn group(self) -> usize {
+        self as usize
+    }
+}
+
+/// A set of `ObjectType`s, stored as a bit per collision group.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ObjectTypeSet(u32);
+
+impl ObjectTypeSet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn all() -> Self {
+        Self::of(&ObjectType::ALL)
+    }
+
+    pub fn of(object_types: &[ObjectType]) -> Self {
+        object_types
+            .iter()
+            .fold(Self::empty(), |set, object_type| set.with(*object_type))
+    }
+
+    pub fn with(self, object_type: ObjectType) -> Self {
+        Self(self.0 | 1 << object_type.group())
+    }
+
+    pub fn without(self, object_type: ObjectType) -> Self {
+        Self(self.0 & !(1 << object_type.group()))
+    }
+
+    pub fn contains(self, object_type: ObjectType) -> bool {
+        self.0 & 1 << object_type.group() != 0
+    }
+
+    fn groups(self) -> Vec<usize> {
+        ObjectType::ALL
+            .iter()
+            .filter(|object_type| self.contains(**object_type))
+            .map(|object_type| object_type.group())
+            .collect()
+    }
+}
+
+/// Which object types are allowed to touch, or overlap for sensors. Always symmetric.
+#[derive(Debug, Clone)]
+pub struct CollisionRules {
+    interacts_with: [ObjectTypeSet; ObjectType::ALL.len()],
+}
+
+impl Default for CollisionRules {
+    fn default() -> Self {
+        let mut rules = Self {
+            interacts_with: [ObjectTypeSet::all(); ObjectType::ALL.len()],
+        };
+        rules.set_interacts(ObjectType::Pickup, ObjectType::Platform, false);
+        rules.set_interacts(ObjectType::Pickup, ObjectType::Pickup, false);
+        rules.set_interacts(ObjectType::Enemy, ObjectType::Enemy, false);
+        // Checkpoints are only there for the players to touch
+        for object_type in ObjectType::ALL.iter().filter(|object_type| **object_type != ObjectType::Player) {
+            rules.set_interacts(ObjectType::Checkpoint, *object_type, false);
+        }
+
+        rules
+    }
+}
+
+impl CollisionRules {
+    pub fn set_interacts(&mut self, a: ObjectType, b: ObjectType, interacts: bool) {
+        let (set_a, set_b) = if interacts {
+            (self.interacts_with[a.group()].with(b), self.interacts_with[b.group()].with(a))
+        } else {
+            (self.interacts_with[a.group()].without(b), self.interacts_with[b.group()].without(a))
+        };
+        self.interacts_with[a.group()] = set_a;
+        self.interacts_with[b.group()] = set_b;
+    }
+
+    pub fn interacts(&self, a: ObjectType, b: ObjectType) -> bool {
+        self.interacts_with[a.group()].contains(b)
+    }
+}
+
+// Serializable so snapshots can match bodies and colliders up again
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct BodyHandle(usize);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct ColliderHandle(usize);
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct GravityZoneHandle(usize);
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct ForceZoneHandle(usize);
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct RopeHandle(usize);
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BodyKind {
+    /// Moved by gravity, forces and collisions.
+    Dynamic,
+    /// Only moves through `set_position` or its velocity, nothing pushes it.
+    Kinematic,
+    Static,
+}
+
+/// A body to add to a `PhysicsBackend`, in pixels.
+#[derive(Debug, Clone)]
+pub struct BodyDesc {
+    pub kind: BodyKind,
+    pub position: Point2<f32>,
+    pub mass: f32,
+    pub gravity_enabled: bool,
+}
+
+impl BodyDesc {
+    pub fn new(kind: BodyKind) -> Self {
+        Self {
+            kind,
+            position: Point2::origin(),
+            mass: 1.,
+            gravity_enabled: true,
+        }
+    }
+
+    pub fn dynamic() -> Self {
+        Self::new(BodyKind::Dynamic)
+    }
+
+    pub fn kinematic() -> Self {
+        Self::new(BodyKind::Kinematic)
+    }
+
+    pub fn fixed() -> Self {
+        Self::new(BodyKind::Static)
+    }
+
+    pub fn position(self, position: Point2<f32>) -> Self {
+        Self {
+            position,
+            ..self
+        }
+    }
+
+    pub fn mass(self, mass: f32) -> Self {
+        Self {
+            mass,
+            ..self
+        }
+    }
+
+    pub fn gravity_enabled(self, gravity_enabled: bool) -> Self {
+        Self {
+            gravity_enabled,
+            ..self
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ColliderShape {
+    Cuboid(Vector2<f32>),
+    Ball(f32),
+}
+
+impl ColliderShape {
+    /// Half extents of the shape's bounding box when turned by `rotation` radians.
+    pub fn half_extents(&self, rotation: f32) -> Vector2<f32> {
+        match *self {
+            ColliderShape::Cuboid(half) => {
+                let (sin, cos) = (rotation.sin().abs(), rotation.cos().abs());
+                Vector2::new(half.x * cos + half.y * sin, half.x * sin + half.y * cos)
+            },
+            ColliderShape::Ball(radius) => Vector2::repeat(radius),
+        }
+    }
+}
+
+/// A collider to attach to a body, in pixels and relative to the body.
+#[derive(Debug, Clone)]
+pub struct ColliderDesc {
+    pub shape: ColliderShape,
+    pub translation: Vector2<f32>,
+    pub rotation: f32,
+    pub sensor: bool,
+}
+
+impl ColliderDesc {
+    pub fn new(shape: ColliderShape) -> Self {
+        Self {
+            shape,
+            translation: Vector2::zeros(),
+            rotation: 0.,
+            sensor: false,
+        }
+    }
+
+    pub fn translation(self, translation: Vector2<f32>) -> Self {
+        Self {
+            translation,
+            ..self
+        }
+    }
+
+    pub fn rotation(self, rotation: f32) -> Self {
+        Self {
+            rotation,
+            ..self
+        }
+    }
+
+    pub fn sensor(self, sensor: bool) -> Self {
+        Self {
+            sensor,
+            ..self
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum PhysicsEventKind {
+    /// Something started overlapping a sensor.
+    ProximityStarted,
+    ProximityStopped,
+    /// Two solid colliders started touching.
+    ContactStarted,
+    ContactStopped,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicsEvent {
+    pub kind: PhysicsEventKind,
+    pub colliders: (ColliderHandle, ColliderHandle),
+    pub entities: (Entity, Entity),
+}
+
+impl PhysicsEvent {
+    pub fn involves(&self, handle: ColliderHandle) -> bool {
+        self.colliders.0 == handle || self.colliders.1 == handle
+    }
+
+    /// The collider on the other side of the event from `handle`.
+    pub fn other(&self, handle: ColliderHandle) -> Option<(ColliderHandle, Entity)> {
+        if self.colliders.0 == handle {
+            Some((self.colliders.1, self.entities.1))
+        } else if self.colliders.1 == handle {
+            Some((self.colliders.0, self.entities.0))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum WallSide {
+    Left,
+    Right,
+}
+
+/// What a contact is to a collider, judged against the gravity it falls with.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ContactKind {
+    Floor,
+    Ceiling,
+    Wall(WallSide),
+}
+
+impl ContactKind {
+    /// `normal` points from the collider towards what it touches, so a floor
+    /// lies along gravity and a ceiling against it.
+    fn classify(normal: Vector2<f32>, down: Vector2<f32>, max_slope: f32) -> Self {
+        let angle = normal.angle(&down);
+
+        if angle <= max_slope {
+            ContactKind::Floor
+        } else if std::f32::consts::PI - angle <= max_slope {
+            ContactKind::Ceiling
+        } else if normal.x > 0. {
+            ContactKind::Wall(WallSide::Right)
+        } else {
+            ContactKind::Wall(WallSide::Left)
+        }
+    }
+}
+
+/// Result of a `raycast` or `shape_cast`, in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryHit {
+    pub collider: ColliderHandle,
+    pub entity: Entity,
+    pub point: Point2<f32>,
+    /// Surface normal of the collider that was hit, pointing back towards the query.
+    pub normal: Vector2<f32>,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueryFilter {
+    /// Usually the collider of whoever is asking, so it doesn't hit itself.
+    pub exclude: Option<ColliderHandle>,
+    pub include_sensors: bool,
+    pub object_types: ObjectTypeSet,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self {
+            exclude: None,
+            include_sensors: false,
+            object_types: ObjectTypeSet::all(),
+        }
+    }
+}
+
+impl QueryFilter {
+    pub fn excluding(handle: ColliderHandle) -> Self {
+        Self {
+            exclude: Some(handle),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_object_types(self, object_types: ObjectTypeSet) -> Self {
+        Self {
+            object_types,
+            ..self
+        }
+    }
+
+    fn accepts(&self, handle: ColliderHandle, entity: Entity, is_sensor: bool) -> bool {
+        self.exclude != Some(handle)
+            && (self.include_sensors || !is_sensor)
+            && self.object_types.contains(entity.kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Removal {
+    Body(BodyHandle),
+    Collider(ColliderHandle),
+    GravityZone(GravityZoneHandle),
+    ForceZone(ForceZoneHandle),
+}
+
+/// Entities keep a clone of this so they can despawn their physics objects in `Drop`,
+/// where the `World` is out of reach. Queued objects are removed before the next step.
+#[derive(Clone, Default)]
+pub struct RemovalQueue(Rc<RefCell<Vec<Removal>>>);
+
+impl RemovalQueue {
+    pub fn remove_body(&self, handle: BodyHandle) {
+        self.0.borrow_mut().push(Removal::Body(handle));
+    }
+
+    pub fn remove_collider(&self, handle: ColliderHandle) {
+        self.0.borrow_mut().push(Removal::Collider(handle));
+    }
+
+    pub fn remove_gravity_zone(&self, handle: GravityZoneHandle) {
+        self.0.borrow_mut().push(Removal::GravityZone(handle));
+    }
+
+    pub fn remove_force_zone(&self, handle: ForceZoneHandle) {
+        self.0.borrow_mut().push(Removal::ForceZone(handle));
+    }
+
+    fn take(&self) -> Vec<Removal> {
+        self.0.replace(Vec::new())
+    }
+}
+
+/// A region of the level where bodies fall with their own gravity instead of the world's.
+#[derive(Debug, Clone)]
+pub struct GravityZone {
+    pub area: AABB<f32>,
+    pub gravity: Vector2<f32>,
+}
+
+impl GravityZone {
+    pub fn new(area: AABB<f32>, gravity: Vector2<f32>) -> Self {
+        Self {
+            area,
+            gravity,
+        }
+    }
+}
+
+/// The gravity zones of a backend, kept in pixels.
+#[derive(Default)]
+struct GravityZones {
+    zones: Vec<(GravityZoneHandle, GravityZone)>,
+    next_handle: usize,
+}
+
+impl GravityZones {
+    fn add(&mut self, zone: GravityZone) -> GravityZoneHandle {
+        let handle = GravityZoneHandle(self.next_handle);
+        self.next_handle += 1;
+        self.zones.push((handle, zone));
+
+        handle
+    }
+
+    fn remove(&mut self, handle: GravityZoneHandle) -> bool {
+        let count = self.zones.len();
+        self.zones.retain(|(zone_handle, _)| *zone_handle != handle);
+
+        self.zones.len() != count
+    }
+
+    fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    fn to_vec(&self) -> Vec<GravityZone> {
+        self.zones.iter().map(|(_, zone)| zone.clone()).collect()
+    }
+
+    /// Zones added later win where they overlap.
+    fn gravity_at(&self, point: Point2<f32>, gravity: Vector2<f32>) -> Vector2<f32> {
+        self.zones
+            .iter()
+            .rev()
+            .find(|(_, zone)| zone.area.contains_local_point(&point))
+            .map_or(gravity, |(_, zone)| zone.gravity)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ForceZoneKind {
+    Wind,
+    Updraft,
+}
+
+/// A region of the level that pushes the bodies inside it, on top of gravity.
+#[derive(Debug, Clone)]
+pub struct ForceZone {
+    pub kind: ForceZoneKind,
+    pub area: AABB<f32>,
+    pub acceleration: Vector2<f32>,
+    /// Seconds from one gust to the next, it blows during the first half of each.
+    /// `None` blows all the time.
+    pub gust_period: Option<f32>,
+}
+
+impl ForceZone {
+    /// Positive `strength` blows to the right.
+    pub fn wind(area: AABB<f32>, strength: f32) -> Self {
+        Self {
+            kind: ForceZoneKind::Wind,
+            area,
+            acceleration: Vector2::new(strength, 0.),
+            gust_period: None,
+        }
+    }
+
+    pub fn updraft(area: AABB<f32>, strength: f32) -> Self {
+        Self {
+            kind: ForceZoneKind::Updraft,
+            area,
+            acceleration: Vector2::new(0., -strength),
+            gust_period: None,
+        }
+    }
+
+    pub fn with_gusts(self, gust_period: f32) -> Self {
+        Self {
+            gust_period: Some(gust_period),
+            ..self
+        }
+    }
+
+    pub fn is_blowing(&self, time: f32) -> bool {
+        match self.gust_period {
+            Some(period) => time % period < period / 2.,
+            None => true,
+        }
+    }
+}
+
+/// Everything the game needs from a physics engine. Positions, velocities and
+/// shapes are in pixels whatever the backend runs in internally.
+pub trait PhysicsBackend {
+    fn step(&mut self);
+    /// Number of steps taken so far.
+    fn ticks(&self) -> usize;
+    /// Seconds simulated so far, the gusts of force zones follow it.
+    fn time(&self) -> f32;
+    /// Winds the clock to a snapshot's, without touching any body.
+    fn set_clock(&mut self, ticks: usize, time: f32);
+    /// Events from the last step.
+    fn events(&self) -> &[PhysicsEvent];
+
+    fn get_gravity(&self) -> Vector2<f32>;
+    fn set_gravity(&mut self, gravity: Vector2<f32>);
+    fn get_max_slope(&self) -> f32;
+    /// Angle in radians, anything steeper than this is treated as a wall.
+    fn set_max_slope(&mut self, max_slope: f32);
+
+    fn add_gravity_zone(&mut self, zone: GravityZone) -> GravityZoneHandle;
+    fn remove_gravity_zone(&mut self, handle: GravityZoneHandle) -> bool;
+    fn gravity_zones(&self) -> Vec<GravityZone>;
+    /// The gravity a body at `point` falls with.
+    fn gravity_at(&self, point: Point2<f32>) -> Vector2<f32>;
+
+    fn add_force_zone(&mut self, zone: ForceZone) -> ForceZoneHandle;
+    fn remove_force_zone(&mut self, handle: ForceZoneHandle) -> bool;
+    /// Every force zone, with whether it is blowing right now.
+    fn force_zones(&self) -> Vec<(ForceZone, bool)>;
+    /// Summed acceleration of the zones blowing at `point`.
+    fn force_at(&self, point: Point2<f32>) -> Vector2<f32>;
+
+    fn add_body(&mut self, desc: BodyDesc) -> BodyHandle;
+    /// Removes the body together with its colliders and ropes.
+    fn remove_body(&mut self, handle: BodyHandle) -> bool;
+    /// `None` once the body has been removed.
+    fn get_position(&self, handle: BodyHandle) -> Option<Point2<f32>>;
+    /// Does nothing if the body has been removed, same for the other setters.
+    fn set_position(&mut self, handle: BodyHandle, position: Point2<f32>);
+    fn get_velocity(&self, handle: BodyHandle) -> Option<Point2<f32>>;
+    fn set_velocity(&mut self, handle: BodyHandle, velocity: Point2<f32>);
+    fn set_gravity_enabled(&mut self, handle: BodyHandle, enabled: bool);
+    /// Every body with its kind, in the order they were added.
+    fn bodies(&self) -> Vec<(BodyHandle, BodyKind)>;
+
+    /// `None` if `body` doesn't exist.
+    fn add_collider(&mut self, body: BodyHandle, desc: ColliderDesc, entity: Entity) -> Option<ColliderHandle>;
+    fn remove_collider(&mut self, handle: ColliderHandle) -> bool;
+    /// The entity a collider belongs to, `None` for removed colliders.
+    fn entity(&self, handle: ColliderHandle) -> Option<Entity>;
+    fn collider_position(&self, handle: ColliderHandle) -> Option<Point2<f32>>;
+    /// Corners of every collider, for debug drawing.
+    fn collider_outlines(&self) -> Vec<Vec<Point2<f32>>>;
+    /// Decides the collision groups of every collider added after it is changed.
+    fn collision_rules_mut(&mut self) -> &mut CollisionRules;
+
+    fn removal_queue(&self) -> RemovalQueue;
+
+    /// Contact normals of every collision with `handle`, pointing from it towards the other collider.
+    fn contact_normals(&self, handle: ColliderHandle) -> Vec<(Entity, Vector2<f32>)>;
+
+    /// Closest collider along `dir` from `origin`, at most `max_dist` away.
+    fn raycast(
+        &self,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit>;
+
+    /// Sweeps `shape` from `origin` along `dir` and returns the first collider it touches.
+    /// A hit with a distance of zero means the shape already overlaps it at `origin`.
+    fn shape_cast(
+        &self,
+        shape: &ColliderShape,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit>;
+
+    /// Hangs `body` from `point` on the collider `hook`, with the rope as long as
+    /// their current distance. Returns `None` if either is gone or they overlap.
+    fn attach_rope(&mut self, body: BodyHandle, hook: ColliderHandle, point: Point2<f32>) -> Option<RopeHandle>;
+    /// Lets go of the rope, the attached body keeps the velocity it had.
+    fn detach_rope(&mut self, handle: RopeHandle) -> bool;
+    /// `None` once the rope has been detached.
+    fn rope_length(&self, handle: RopeHandle) -> Option<f32>;
+    /// Shortening the rope pulls the attached body in.
+    fn set_rope_length(&mut self, handle: RopeHandle, length: f32);
+    /// The collider the rope hangs from and where, `None` once it has been detached.
+    fn rope_hook(&self, handle: RopeHandle) -> Option<(ColliderHandle, Point2<f32>)>;
+    /// Hook and attached body of every rope.
+    fn ropes(&self) -> Vec<(Point2<f32>, Point2<f32>)>;
+
+    /// Adds a collider that reports overlaps through `events` instead of blocking movement.
+    fn add_sensor(&mut self, body: BodyHandle, desc: ColliderDesc, entity: Entity) -> Option<ColliderHandle> {
+        self.add_collider(body, desc.sensor(true), entity)
+    }
+
+    /// Removes everything queued through a `RemovalQueue` since the last step.
+    fn flush_removals(&mut self) {
+        for removal in self.removal_queue().take() {
+            match removal {
+                Removal::Body(handle) => { self.remove_body(handle); },
+                Removal::Collider(handle) => { self.remove_collider(handle); },
+                Removal::GravityZone(handle) => { self.remove_gravity_zone(handle); },
+                Removal::ForceZone(handle) => { self.remove_force_zone(handle); },
+            }
+        }
+    }
+
+    /// Every contact of `handle` with colliders of the given types, classified
+    /// against the gravity at the collider's position.
+    fn contact_kinds(
+        &self,
+        handle: ColliderHandle,
+        object_types: ObjectTypeSet,
+    ) -> Vec<(Entity, ContactKind)> {
+        let position = match self.collider_position(handle) {
+            Some(position) => position,
+            None => return Vec::new(),
+        };
+        // Without gravity there is no floor to speak of, fall back to screen down
+        let down = self.gravity_at(position)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| Vector2::new(0., 1.));
+        let max_slope = self.get_max_slope();
+
+        self.contact_normals(handle)
+            .into_iter()
+            .filter(|(other, _)| object_types.contains(other.kind))
+            .map(|(other, normal)| (other, ContactKind::classify(normal, down, max_slope)))
+            .collect()
+    }
+
+    /// True if `handle` stands on a collider of one of the `ground` types.
+    fn ground_check(&self, handle: ColliderHandle, ground: ObjectTypeSet) -> bool {
+        self.contact_kinds(handle, ground)
+            .iter()
+            .any(|(_, kind)| *kind == ContactKind::Floor)
+    }
+
+    fn ceiling_check(&self, handle: ColliderHandle, ceiling: ObjectTypeSet) -> bool {
+        self.contact_kinds(handle, ceiling)
+            .iter()
+            .any(|(_, kind)| *kind == ContactKind::Ceiling)
+    }
+
+    fn wall_check(&self, handle: ColliderHandle) -> Option<WallSide> {
+        self.contact_kinds(handle, ObjectTypeSet::of(&[ObjectType::Platform]))
+            .into_iter()
+            .find_map(|(_, kind)| match kind {
+                ContactKind::Wall(side) => Some(side),
+                _ => None,
+            })
+    }
+}
+
+/// Which `PhysicsBackend` the game runs on, picked at startup.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PhysicsKind {
+    NPhysics,
+    Aabb,
+}
+
+// Deriving it needs `#[default]`, which is newer than the compilers ggez 0.5 builds on
+impl Default for PhysicsKind {
+    fn default() -> Self {
+        PhysicsKind::NPhysics
+    }
+}
+
+impl FromStr for PhysicsKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "nphysics" => Ok(PhysicsKind::NPhysics),
+            "aabb" => Ok(PhysicsKind::Aabb),
+            _ => Err(format!("Unknown physics backend \"{}\", expected nphysics or aabb", name)),
+        }
+    }
+}
+
+impl PhysicsKind {
+    pub fn create(self) -> Box<dyn PhysicsBackend> {
+        match self {
+            PhysicsKind::NPhysics => Box::new(NPhysicsBackend::new()),
+            PhysicsKind::Aabb => Box::new(AabbBackend::new()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,881 @@
+use crate::{util, entity::Entity};
+use super::*;
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use nphysics2d::world::{DefaultMechanicalWorld, DefaultGeometricalWorld};
+use nphysics2d::algebra::{Force2, ForceType};
+use nphysics2d::object::{self, Body, BodySet, BodyStatus, DefaultBodySet, DefaultColliderSet, DefaultBodyHandle, DefaultColliderHandle, Collider, BodyPartHandle, RigidBodyDesc};
+use nphysics2d::joint::{RevoluteConstraint, DefaultJointConstraintHandle, DefaultJointConstraintSet, PrismaticConstraint};
+use nphysics2d::force_generator::{DefaultForceGeneratorHandle, DefaultForceGeneratorSet, ForceGenerator};
+use nphysics2d::solver::{IntegrationParameters, SignoriniModel};
+
+use ncollide2d::query::{self, Proximity, Ray};
+use ncollide2d::pipeline::{ContactEvent, ProximityEvent};
+use ncollide2d::bounding_volume::{AABB, BoundingVolume};
+use ncollide2d::pipeline::CollisionGroups;
+use ncollide2d::shape::{Ball, Cuboid, ShapeHandle};
+
+use nalgebra as na;
+use na::{Point2, Vector2};
+
+const PIXELS_PER_METER: f32 = 20.;
+/// Mass of each hidden body in a rope, small enough not to drag on what hangs from it.
+const ROPE_LINK_MASS: f32 = 0.1;
+
+impl CollisionRules {
+    fn collision_groups(&self, object_type: ObjectType) -> CollisionGroups {
+        CollisionGroups::new()
+            .with_membership(&[object_type.group()])
+            .with_whitelist(&self.interacts_with[object_type.group()].groups())
+    }
+}
+
+impl QueryFilter {
+    fn collision_groups(&self) -> CollisionGroups {
+        CollisionGroups::new().with_whitelist(&self.object_types.groups())
+    }
+}
+
+/// What the backend knows about a collider besides what nphysics stores.
+struct ColliderInfo {
+    handle: DefaultColliderHandle,
+    body: BodyHandle,
+    entity: Entity,
+}
+
+/// nphysics has no rope joint, so a rope is a chain: `link` is pinned to the hook
+/// and swings around it, `slider` moves along `link` through a prismatic joint whose max
+/// offset is the rope's length, and the attached body is pinned to `slider`.
+struct Rope {
+    hook: ColliderHandle,
+    hooked: BodyHandle,
+    hooked_part: BodyPartHandle<DefaultBodyHandle>,
+    /// Local to the hooked body, in meters.
+    anchor: Point2<f32>,
+    attached: BodyHandle,
+    link: DefaultBodyHandle,
+    slider: DefaultBodyHandle,
+    prismatic: DefaultJointConstraintHandle,
+}
+
+/// Drives a `ForceZone` from the mechanical world's step, the zone is kept in meters.
+struct ForceZoneGenerator {
+    zone: ForceZone,
+    time: f32,
+}
+
+impl ForceZoneGenerator {
+    fn is_blowing(&self) -> bool {
+        self.zone.is_blowing(self.time)
+    }
+}
+
+impl ForceGenerator<f32, DefaultBodyHandle> for ForceZoneGenerator {
+    fn apply(
+        &mut self,
+        parameters: &IntegrationParameters<f32>,
+        bodies: &mut dyn BodySet<f32, Handle = DefaultBodyHandle>,
+    ) {
+        self.time += parameters.dt();
+        if !self.is_blowing() {
+            return;
+        }
+
+        let zone = &self.zone;
+        // Same bodies as the gravity zones, a dash cuts straight through
+        bodies.foreach_mut(&mut |_, body| {
+            if body.status() != BodyStatus::Dynamic || !body.gravity_enabled() {
+                return;
+            }
+
+            let inside = body.part(0)
+                .map(|part| zone.area.contains_local_point(&util::isometry_to_point(part.position())))
+                .unwrap_or(false);
+            if inside {
+                body.apply_force(0, &Force2::linear(zone.acceleration), ForceType::AccelerationChange, true);
+            }
+        });
+    }
+}
+
+pub struct NPhysicsBackend {
+    /**
+     * The mechanical world contains all the data structures and algorithms necessary to
+     * perform efficiently the simulation of physical phenomena like
+     * gravity, contact forces, deformations, etc.
+    */
+    mechanical_world: DefaultMechanicalWorld<f32>,
+
+    /**
+     * The geometrical world contains all the data structures and algorithms necessary to perform efficiently
+     * geometric operations like computing sets of contacts between touching objects, detecting when two objects
+     * start interacting with each other, etc.
+    */
+    geometrical_world: DefaultGeometricalWorld<f32>,
+
+    bodies: DefaultBodySet<f32>,
+    colliders: DefaultColliderSet<f32>,
+    joint_constraint_set: DefaultJointConstraintSet<f32>,
+    force_generator_set: DefaultForceGeneratorSet<f32>,
+
+    /// The game's handles, mapped to the ones nphysics hands out.
+    body_handles: HashMap<BodyHandle, DefaultBodyHandle>,
+    collider_infos: HashMap<ColliderHandle, ColliderInfo>,
+    collider_handles: HashMap<DefaultColliderHandle, ColliderHandle>,
+    force_zone_handles: HashMap<ForceZoneHandle, DefaultForceGeneratorHandle>,
+    next_handle: usize,
+
+    /// Everything outside this module is in pixels, the simulation itself runs in meters.
+    pixels_per_meter: f32,
+    gravity: Vector2<f32>,
+    max_slope: f32,
+    gravity_zones: GravityZones,
+    ropes: Vec<(RopeHandle, Rope)>,
+    removal_queue: RemovalQueue,
+    collision_rules: CollisionRules,
+    events: Vec<PhysicsEvent>,
+    time: f32,
+    ticks: usize,
+}
+
+impl NPhysicsBackend {
+    pub fn new() -> Self {
+        Self::with_scale(PIXELS_PER_METER)
+    }
+
+    pub fn with_scale(pixels_per_meter: f32) -> Self {
+        let gravity = Vector2::new(0.0, DEFAULT_GRAVITY);
+        let mut mechanical_world = DefaultMechanicalWorld::new(
+            util::pixels_to_meters(gravity, pixels_per_meter)
+        );
+        mechanical_world.set_timestep(TIME_STEP);
+        mechanical_world
+            .solver
+            .set_contact_model(Box::new(SignoriniModel::new()));
+
+        let geometrical_world = DefaultGeometricalWorld::new();
+
+        let bodies = DefaultBodySet::new();
+        let colliders = DefaultColliderSet::new();
+
+        let joint_constraint_set = DefaultJointConstraintSet::new();
+        let force_generator_set = DefaultForceGeneratorSet::new();
+
+        Self {
+            geometrical_world,
+            mechanical_world,
+            bodies,
+            colliders,
+            joint_constraint_set,
+            force_generator_set,
+            body_handles: HashMap::new(),
+            collider_infos: HashMap::new(),
+            collider_handles: HashMap::new(),
+            force_zone_handles: HashMap::new(),
+            next_handle: 0,
+            pixels_per_meter,
+            gravity,
+            max_slope: DEFAULT_MAX_SLOPE,
+            gravity_zones: GravityZones::default(),
+            ropes: Vec::new(),
+            removal_queue: RemovalQueue::default(),
+            collision_rules: CollisionRules::default(),
+            events: Vec::new(),
+            time: 0.,
+            ticks: 0,
+        }
+    }
+
+    pub fn pixels_per_meter(&self) -> f32 {
+        self.pixels_per_meter
+    }
+
+    fn to_meters<T: std::ops::Div<f32, Output = T>>(&self, pixels: T) -> T {
+        util::pixels_to_meters(pixels, self.pixels_per_meter)
+    }
+
+    fn to_pixels<T: std::ops::Mul<f32, Output = T>>(&self, meters: T) -> T {
+        util::meters_to_pixels(meters, self.pixels_per_meter)
+    }
+
+    fn next_handle(&mut self) -> usize {
+        self.next_handle += 1;
+        self.next_handle
+    }
+
+    fn rigid_body(&self, handle: BodyHandle) -> Option<&object::RigidBody<f32>> {
+        self.bodies.rigid_body(*self.body_handles.get(&handle)?)
+    }
+
+    fn rigid_body_mut(&mut self, handle: BodyHandle) -> Option<&mut object::RigidBody<f32>> {
+        self.bodies.rigid_body_mut(*self.body_handles.get(&handle)?)
+    }
+
+    fn collider(&self, handle: ColliderHandle) -> Option<&Collider<f32, DefaultBodyHandle>> {
+        self.colliders.get(self.collider_infos.get(&handle)?.handle)
+    }
+
+    /// Our handle and entity of an nphysics collider.
+    fn collider_entity(&self, handle: DefaultColliderHandle) -> Option<(ColliderHandle, Entity)> {
+        let collider = *self.collider_handles.get(&handle)?;
+
+        Some((collider, self.collider_infos.get(&collider)?.entity))
+    }
+
+    fn collect_events(&mut self) {
+        let mut events = Vec::new();
+
+        for event in self.geometrical_world.proximity_events().iter() {
+            let ProximityEvent { collider1, collider2, prev_status, new_status } = *event;
+
+            let kind = match (prev_status, new_status) {
+                (Proximity::Intersecting, Proximity::Intersecting) => continue,
+                (_, Proximity::Intersecting) => PhysicsEventKind::ProximityStarted,
+                (Proximity::Intersecting, _) => PhysicsEventKind::ProximityStopped,
+                _ => continue,
+            };
+            events.extend(self.new_event(kind, collider1, collider2));
+        }
+
+        for event in self.geometrical_world.contact_events().iter() {
+            let event = match *event {
+                ContactEvent::Started(collider1, collider2) => {
+                    self.new_event(PhysicsEventKind::ContactStarted, collider1, collider2)
+                },
+                ContactEvent::Stopped(collider1, collider2) => {
+                    self.new_event(PhysicsEventKind::ContactStopped, collider1, collider2)
+                },
+            };
+            events.extend(event);
+        }
+
+        self.events = events;
+    }
+
+    fn new_event(
+        &self,
+        kind: PhysicsEventKind,
+        collider1: DefaultColliderHandle,
+        collider2: DefaultColliderHandle,
+    ) -> Option<PhysicsEvent> {
+        // Colliders removed during the step can still show up in its events,
+        // they no longer have an entity
+        let (handle1, entity1) = self.collider_entity(collider1)?;
+        let (handle2, entity2) = self.collider_entity(collider2)?;
+
+        Some(PhysicsEvent {
+            kind,
+            colliders: (handle1, handle2),
+            entities: (entity1, entity2),
+        })
+    }
+
+    /// The mechanical world only knows the global gravity, so bodies inside a zone
+    /// get the difference applied as an extra acceleration for this step.
+    fn apply_gravity_zones(&mut self) {
+        if self.gravity_zones.is_empty() {
+            return;
+        }
+
+        let handles: Vec<DefaultBodyHandle> = self.bodies.iter().map(|(handle, _)| handle).collect();
+        for handle in handles {
+            let position = match self.bodies.rigid_body(handle) {
+                Some(rb) if rb.status() == BodyStatus::Dynamic && rb.gravity_enabled() => {
+                    self.to_pixels(util::isometry_to_point(*rb.position()))
+                },
+                _ => continue,
+            };
+
+            let correction = self.to_meters(self.gravity_at(position) - self.gravity);
+            if correction == Vector2::zeros() {
+                continue;
+            }
+
+            if let Some(rb) = self.bodies.rigid_body_mut(handle) {
+                rb.apply_force(0, &Force2::linear(correction), ForceType::AccelerationChange, true);
+            }
+        }
+    }
+
+    fn force_zone_generators(&self) -> impl Iterator<Item = &ForceZoneGenerator> {
+        self.force_generator_set
+            .iter()
+            .filter_map(|(_, generator)| generator.downcast_ref::<ForceZoneGenerator>())
+    }
+
+    fn scale_shape(&self, shape: &ColliderShape) -> ShapeHandle<f32> {
+        match *shape {
+            ColliderShape::Cuboid(half_extents) => ShapeHandle::new(Cuboid::new(self.to_meters(half_extents))),
+            ColliderShape::Ball(radius) => ShapeHandle::new(Ball::new(self.to_meters(radius))),
+        }
+    }
+
+    /// `desc` is in pixels.
+    fn rigid_body_desc(&self, desc: &BodyDesc) -> RigidBodyDesc<f32> {
+        let status = match desc.kind {
+            BodyKind::Dynamic => BodyStatus::Dynamic,
+            BodyKind::Kinematic => BodyStatus::Kinematic,
+            BodyKind::Static => BodyStatus::Static,
+        };
+
+        RigidBodyDesc::new()
+            .status(status)
+            .translation(self.to_meters(desc.position).coords)
+            .mass(desc.mass)
+            .gravity_enabled(desc.gravity_enabled)
+    }
+
+    /// Removes an nphysics body and every joint attached to it.
+    fn remove_rigid_body(&mut self, handle: DefaultBodyHandle) -> bool {
+        let joints: Vec<_> = self.joint_constraint_set
+            .iter()
+            .filter(|(_, joint)| {
+                let (anchor1, anchor2) = joint.anchors();
+                anchor1.0 == handle || anchor2.0 == handle
+            })
+            .map(|(joint_handle, _)| joint_handle)
+            .collect();
+        for joint_handle in joints {
+            self.joint_constraint_set.remove(joint_handle);
+        }
+
+        self.bodies.remove(handle).is_some()
+    }
+
+    fn rope(&self, handle: RopeHandle) -> Option<&Rope> {
+        self.ropes
+            .iter()
+            .find(|(rope_handle, _)| *rope_handle == handle)
+            .map(|(_, rope)| rope)
+    }
+}
+
+impl PhysicsBackend for NPhysicsBackend {
+    fn step(&mut self) {
+        self.flush_removals();
+        self.apply_gravity_zones();
+
+        self.mechanical_world.step(
+            &mut self.geometrical_world,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joint_constraint_set,
+            &mut self.force_generator_set,
+        );
+
+        self.collect_events();
+        self.time += TIME_STEP;
+        self.ticks += 1;
+    }
+
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn set_clock(&mut self, ticks: usize, time: f32) {
+        self.ticks = ticks;
+        self.time = time;
+
+        // Every generator keeps its own copy of the clock
+        for (_, generator) in self.force_generator_set.iter_mut() {
+            if let Some(generator) = generator.downcast_mut::<ForceZoneGenerator>() {
+                generator.time = time;
+            }
+        }
+    }
+
+    fn events(&self) -> &[PhysicsEvent] {
+        &self.events
+    }
+
+    fn get_gravity(&self) -> Vector2<f32> {
+        self.gravity
+    }
+
+    fn set_gravity(&mut self, gravity: Vector2<f32>) {
+        self.gravity = gravity;
+        self.mechanical_world.gravity = self.to_meters(gravity);
+    }
+
+    fn get_max_slope(&self) -> f32 {
+        self.max_slope
+    }
+
+    fn set_max_slope(&mut self, max_slope: f32) {
+        self.max_slope = max_slope;
+    }
+
+    fn add_gravity_zone(&mut self, zone: GravityZone) -> GravityZoneHandle {
+        self.gravity_zones.add(zone)
+    }
+
+    fn remove_gravity_zone(&mut self, handle: GravityZoneHandle) -> bool {
+        self.gravity_zones.remove(handle)
+    }
+
+    fn gravity_zones(&self) -> Vec<GravityZone> {
+        self.gravity_zones.to_vec()
+    }
+
+    fn gravity_at(&self, point: Point2<f32>) -> Vector2<f32> {
+        self.gravity_zones.gravity_at(point, self.gravity)
+    }
+
+    fn add_force_zone(&mut self, zone: ForceZone) -> ForceZoneHandle {
+        let zone = ForceZone {
+            area: AABB::new(self.to_meters(*zone.area.mins()), self.to_meters(*zone.area.maxs())),
+            acceleration: self.to_meters(zone.acceleration),
+            ..zone
+        };
+
+        let generator = self.force_generator_set.insert(Box::new(ForceZoneGenerator {
+            zone,
+            time: self.time,
+        }));
+        let handle = ForceZoneHandle(self.next_handle());
+        self.force_zone_handles.insert(handle, generator);
+
+        handle
+    }
+
+    fn remove_force_zone(&mut self, handle: ForceZoneHandle) -> bool {
+        match self.force_zone_handles.remove(&handle) {
+            Some(generator) => self.force_generator_set.remove(generator).is_some(),
+            None => false,
+        }
+    }
+
+    fn force_zones(&self) -> Vec<(ForceZone, bool)> {
+        self.force_zone_generators()
+            .map(|generator| {
+                let zone = ForceZone {
+                    area: AABB::new(
+                        self.to_pixels(*generator.zone.area.mins()),
+                        self.to_pixels(*generator.zone.area.maxs()),
+                    ),
+                    acceleration: self.to_pixels(generator.zone.acceleration),
+                    ..generator.zone.clone()
+                };
+
+                (zone, generator.is_blowing())
+            })
+            .collect()
+    }
+
+    fn force_at(&self, point: Point2<f32>) -> Vector2<f32> {
+        let point = self.to_meters(point);
+        let acceleration = self.force_zone_generators()
+            .filter(|generator| generator.is_blowing() && generator.zone.area.contains_local_point(&point))
+            .fold(Vector2::zeros(), |sum, generator| sum + generator.zone.acceleration);
+
+        self.to_pixels(acceleration)
+    }
+
+    fn add_body(&mut self, desc: BodyDesc) -> BodyHandle {
+        let rigid_body = self.bodies.insert(self.rigid_body_desc(&desc).build());
+        let handle = BodyHandle(self.next_handle());
+        self.body_handles.insert(handle, rigid_body);
+
+        handle
+    }
+
+    fn remove_body(&mut self, handle: BodyHandle) -> bool {
+        let ropes: Vec<_> = self.ropes
+            .iter()
+            .filter(|(_, rope)| rope.hooked == handle || rope.attached == handle)
+            .map(|(rope_handle, _)| *rope_handle)
+            .collect();
+        for rope_handle in ropes {
+            self.detach_rope(rope_handle);
+        }
+
+        let colliders: Vec<_> = self.collider_infos
+            .iter()
+            .filter(|(_, info)| info.body == handle)
+            .map(|(collider_handle, _)| *collider_handle)
+            .collect();
+        for collider_handle in colliders {
+            self.remove_collider(collider_handle);
+        }
+
+        match self.body_handles.remove(&handle) {
+            Some(rigid_body) => self.remove_rigid_body(rigid_body),
+            None => false,
+        }
+    }
+
+    fn get_position(&self, handle: BodyHandle) -> Option<Point2<f32>> {
+        let rb = self.rigid_body(handle)?;
+
+        Some(self.to_pixels(util::isometry_to_point(*rb.position())))
+    }
+
+    fn set_position(&mut self, handle: BodyHandle, point: Point2<f32>) {
+        let point = self.to_meters(point);
+        if let Some(rb) = self.rigid_body_mut(handle) {
+            rb.set_position(util::point_to_isometry(point));
+        }
+    }
+
+    fn get_velocity(&self, handle: BodyHandle) -> Option<Point2<f32>> {
+        let velocity: Point2<f32> = self.rigid_body(handle)?
+            .velocity()
+            .linear
+            .into();
+
+        Some(self.to_pixels(velocity))
+    }
+
+    fn set_velocity(&mut self, handle: BodyHandle, velocity: Point2<f32>) {
+        let velocity = self.to_meters(velocity);
+        if let Some(rb) = self.rigid_body_mut(handle) {
+            rb.set_linear_velocity(velocity.coords);
+        }
+    }
+
+    fn set_gravity_enabled(&mut self, handle: BodyHandle, enabled: bool) {
+        if let Some(rb) = self.rigid_body_mut(handle) {
+            rb.enable_gravity(enabled);
+        }
+    }
+
+    fn bodies(&self) -> Vec<(BodyHandle, BodyKind)> {
+        let mut bodies: Vec<_> = self.body_handles
+            .keys()
+            .filter_map(|handle| {
+                let kind = match self.rigid_body(*handle)?.status() {
+                    BodyStatus::Dynamic => BodyKind::Dynamic,
+                    BodyStatus::Kinematic => BodyKind::Kinematic,
+                    _ => BodyKind::Static,
+                };
+
+                Some((*handle, kind))
+            })
+            .collect();
+        // Handles count up, so sorting them restores the order the bodies were added in
+        bodies.sort_by_key(|(handle, _)| *handle);
+
+        bodies
+    }
+
+    fn add_collider(&mut self, body: BodyHandle, desc: ColliderDesc, entity: Entity) -> Option<ColliderHandle> {
+        let rigid_body = *self.body_handles.get(&body)?;
+
+        let collider = object::ColliderDesc::new(self.scale_shape(&desc.shape))
+            .translation(self.to_meters(desc.translation))
+            .rotation(desc.rotation)
+            .sensor(desc.sensor)
+            .collision_groups(self.collision_rules.collision_groups(entity.kind))
+            .build(BodyPartHandle(rigid_body, 0));
+
+        let collider = self.colliders.insert(collider);
+        let handle = ColliderHandle(self.next_handle());
+        self.collider_infos.insert(handle, ColliderInfo {
+            handle: collider,
+            body,
+            entity,
+        });
+        self.collider_handles.insert(collider, handle);
+
+        Some(handle)
+    }
+
+    fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
+        match self.collider_infos.remove(&handle) {
+            Some(info) => {
+                self.collider_handles.remove(&info.handle);
+                self.colliders.remove(info.handle).is_some()
+            },
+            None => false,
+        }
+    }
+
+    fn entity(&self, handle: ColliderHandle) -> Option<Entity> {
+        self.collider_infos.get(&handle).map(|info| info.entity)
+    }
+
+    fn collider_position(&self, handle: ColliderHandle) -> Option<Point2<f32>> {
+        let collider = self.collider(handle)?;
+
+        Some(self.to_pixels(util::isometry_to_point(*collider.position())))
+    }
+
+    /// Rotated cuboids keep their rotation.
+    fn collider_outlines(&self) -> Vec<Vec<Point2<f32>>> {
+        self.colliders
+            .iter()
+            .map(|(_, collider)| {
+                let corners = match collider.shape().as_shape::<Cuboid<f32>>() {
+                    Some(cuboid) => {
+                        let half = cuboid.half_extents();
+                        vec!{
+                            Point2::new(-half.x, -half.y),
+                            Point2::new(half.x, -half.y),
+                            Point2::new(half.x, half.y),
+                            Point2::new(-half.x, half.y),
+                        }
+                    },
+                    None => {
+                        let aabb = collider.shape().local_aabb();
+                        let (mins, maxs) = (aabb.mins(), aabb.maxs());
+                        vec!{
+                            Point2::new(mins.x, mins.y),
+                            Point2::new(maxs.x, mins.y),
+                            Point2::new(maxs.x, maxs.y),
+                            Point2::new(mins.x, maxs.y),
+                        }
+                    },
+                };
+
+                corners
+                    .into_iter()
+                    .map(|corner| self.to_pixels(collider.position() * corner))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn collision_rules_mut(&mut self) -> &mut CollisionRules {
+        &mut self.collision_rules
+    }
+
+    fn removal_queue(&self) -> RemovalQueue {
+        self.removal_queue.clone()
+    }
+
+    fn contact_normals(&self, handle: ColliderHandle) -> Vec<(Entity, Vector2<f32>)> {
+        let collider = match self.collider_infos.get(&handle) {
+            Some(info) => info.handle,
+            None => return Vec::new(),
+        };
+
+        self.geometrical_world
+            .contacts_with(&self.colliders, collider, true)
+            .into_iter()
+            .flatten()
+            .flat_map(|(handle1, _, handle2, _, _, manifold)| {
+                let (other, sign) = if handle1 == collider {
+                    (handle2, 1.)
+                } else {
+                    (handle1, -1.)
+                };
+                let other = self.collider_entity(other).map(|(_, entity)| entity);
+
+                manifold
+                    .contacts()
+                    .filter_map(move |tracked_contact| Some((other?, *tracked_contact.contact.normal * sign)))
+            })
+            .collect()
+    }
+
+    fn raycast(
+        &self,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit> {
+        let dir = dir.try_normalize(f32::EPSILON)?;
+        let ray = Ray::new(self.to_meters(origin), dir);
+        let groups = filter.collision_groups();
+
+        self.geometrical_world
+            .interferences_with_ray(&self.colliders, &ray, self.to_meters(max_dist), &groups)
+            .filter_map(|(collider, shape, intersection)| {
+                let (handle, entity) = self.collider_entity(collider)?;
+                if !filter.accepts(handle, entity, shape.is_sensor()) {
+                    return None;
+                }
+
+                Some((handle, entity, intersection))
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal))
+            .map(|(handle, entity, intersection)| {
+                QueryHit {
+                    collider: handle,
+                    entity,
+                    point: self.to_pixels(ray.point_at(intersection.toi)),
+                    normal: intersection.normal,
+                    distance: self.to_pixels(intersection.toi),
+                }
+            })
+    }
+
+    fn shape_cast(
+        &self,
+        shape: &ColliderShape,
+        origin: Point2<f32>,
+        dir: Vector2<f32>,
+        max_dist: f32,
+        filter: &QueryFilter,
+    ) -> Option<QueryHit> {
+        let dir = dir.try_normalize(f32::EPSILON).unwrap_or_else(Vector2::zeros);
+        let shape = self.scale_shape(shape);
+        let max_dist = self.to_meters(max_dist);
+
+        let start = util::point_to_isometry(self.to_meters(origin));
+        let end = util::point_to_isometry(self.to_meters(origin) + dir * max_dist);
+        let swept_aabb = shape.aabb(&start).merged(&shape.aabb(&end));
+        let groups = filter.collision_groups();
+
+        self.geometrical_world
+            .interferences_with_aabb(&self.colliders, &swept_aabb, &groups)
+            .filter_map(|(collider_handle, collider)| {
+                let (handle, entity) = self.collider_entity(collider_handle)?;
+                if !filter.accepts(handle, entity, collider.is_sensor()) {
+                    return None;
+                }
+
+                let toi = query::time_of_impact(
+                    &start,
+                    &dir,
+                    &*shape,
+                    collider.position(),
+                    &Vector2::zeros(),
+                    collider.shape(),
+                    max_dist,
+                    0.,
+                )?;
+
+                Some((handle, entity, collider, toi))
+            })
+            .min_by(|(_, _, _, a), (_, _, _, b)| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal))
+            .map(|(handle, entity, collider, toi)| {
+                let position = util::point_to_isometry(self.to_meters(origin) + dir * toi.toi);
+
+                QueryHit {
+                    collider: handle,
+                    entity,
+                    point: self.to_pixels(position * toi.witness1),
+                    normal: collider.position() * *toi.normal2,
+                    distance: self.to_pixels(toi.toi),
+                }
+            })
+    }
+
+    fn attach_rope(
+        &mut self,
+        body: BodyHandle,
+        hook: ColliderHandle,
+        point: Point2<f32>,
+    ) -> Option<RopeHandle> {
+        let hooked = self.collider_infos.get(&hook)?.body;
+        let hooked_part = BodyPartHandle(*self.body_handles.get(&hooked)?, 0);
+        let attached_part = BodyPartHandle(*self.body_handles.get(&body)?, 0);
+        let hook_position = self.bodies.get(hooked_part.0)?.part(0)?.position();
+        let body_position = util::isometry_to_point(*self.rigid_body(body)?.position());
+
+        let point_meters = self.to_meters(point);
+        let offset = body_position - point_meters;
+        let length = offset.norm();
+        let axis = na::Unit::try_new(offset, f32::EPSILON)?;
+
+        let link_desc = self.rigid_body_desc(&BodyDesc::dynamic().position(point).gravity_enabled(false))
+            .mass(ROPE_LINK_MASS)
+            .angular_inertia(ROPE_LINK_MASS);
+        let slider_desc = link_desc.clone()
+            .translation(body_position.coords);
+        let link = self.bodies.insert(link_desc.build());
+        let slider = self.bodies.insert(slider_desc.build());
+
+        let mut prismatic = PrismaticConstraint::new(
+            BodyPartHandle(link, 0),
+            BodyPartHandle(slider, 0),
+            Point2::origin(),
+            axis,
+            Point2::origin(),
+        );
+        prismatic.enable_min_offset(0.);
+        prismatic.enable_max_offset(length);
+
+        let anchor = hook_position.inverse_transform_point(&point_meters);
+        self.joint_constraint_set.insert(RevoluteConstraint::new(
+            hooked_part,
+            BodyPartHandle(link, 0),
+            anchor,
+            Point2::origin(),
+        ));
+        let prismatic = self.joint_constraint_set.insert(prismatic);
+        self.joint_constraint_set.insert(RevoluteConstraint::new(
+            BodyPartHandle(slider, 0),
+            attached_part,
+            Point2::origin(),
+            Point2::origin(),
+        ));
+
+        let handle = RopeHandle(self.next_handle());
+        self.ropes.push((handle, Rope {
+            hook,
+            hooked,
+            hooked_part,
+            anchor,
+            attached: body,
+            link,
+            slider,
+            prismatic,
+        }));
+
+        Some(handle)
+    }
+
+    fn detach_rope(&mut self, handle: RopeHandle) -> bool {
+        let index = match self.ropes.iter().position(|(rope_handle, _)| *rope_handle == handle) {
+            Some(index) => index,
+            None => return false,
+        };
+        let (_, rope) = self.ropes.remove(index);
+
+        self.remove_rigid_body(rope.link);
+        self.remove_rigid_body(rope.slider);
+
+        true
+    }
+
+    fn rope_length(&self, handle: RopeHandle) -> Option<f32> {
+        let length = self.joint_constraint_set
+            .get(self.rope(handle)?.prismatic)?
+            .downcast_ref::<PrismaticConstraint<f32, DefaultBodyHandle>>()?
+            .max_offset()?;
+
+        Some(self.to_pixels(length))
+    }
+
+    fn set_rope_length(&mut self, handle: RopeHandle, length: f32) {
+        let length = self.to_meters(length);
+        let prismatic = match self.rope(handle) {
+            Some(rope) => rope.prismatic,
+            None => return,
+        };
+
+        let constraint = self.joint_constraint_set
+            .get_mut(prismatic)
+            .and_then(|joint| joint.downcast_mut::<PrismaticConstraint<f32, DefaultBodyHandle>>());
+        if let Some(constraint) = constraint {
+            constraint.enable_max_offset(length);
+        }
+    }
+
+    fn rope_hook(&self, handle: RopeHandle) -> Option<(ColliderHandle, Point2<f32>)> {
+        let rope = self.rope(handle)?;
+        let point = self.bodies.get(rope.hooked_part.0)?.part(0)?.position() * rope.anchor;
+
+        Some((rope.hook, self.to_pixels(point)))
+    }
+
+    fn ropes(&self) -> Vec<(Point2<f32>, Point2<f32>)> {
+        self.ropes
+            .iter()
+            .filter_map(|(_, rope)| {
+                let hook = self.bodies.get(rope.hooked_part.0)?.part(0)?.position() * rope.anchor;
+                let attached = self.rigid_body(rope.attached)?.position();
+
+                Some((self.to_pixels(hook), self.to_pixels(util::isometry_to_point(*attached))))
+            })
+            .collect()
+    }
+}
\ No newline at end of file
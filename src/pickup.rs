@@ -0,0 +1,250 @@
+use crate::{Context, graphics, audio, entity::Entity, player::Ability, power_up::PowerUpKind, physics::{BodyDesc, BodyHandle, ColliderDesc, ColliderHandle, ColliderShape, ObjectType, RemovalQueue}, snapshot::PickupSnapshot, util, World};
+use ggez::audio::SoundSource;
+use nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+
+const PICKUP_RADIUS: f32 = 6.;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PickupKind {
+    Coin,
+    Gem,
+    /// Which one is kept in `Pickup::power_up`.
+    PowerUp,
+    /// Which one is kept in `Pickup::ability`.
+    Ability,
+}
+
+impl PickupKind {
+    pub const ALL: [PickupKind; 4] = [
+        PickupKind::Coin,
+        PickupKind::Gem,
+        PickupKind::PowerUp,
+        PickupKind::Ability,
+    ];
+
+    pub fn points(self) -> u32 {
+        match self {
+            PickupKind::Coin => 10,
+            PickupKind::Gem => 50,
+            PickupKind::PowerUp => 20,
+            PickupKind::Ability => 20,
+        }
+    }
+
+    pub fn color(self) -> graphics::Color {
+        match self {
+            PickupKind::Coin => graphics::Color::from((250, 210, 50, 255)),
+            PickupKind::Gem => graphics::Color::from((80, 230, 220, 255)),
+            PickupKind::PowerUp => graphics::Color::from((200, 120, 255, 255)),
+            PickupKind::Ability => graphics::Color::from((255, 150, 60, 255)),
+        }
+    }
+}
+
+/// Something the players collect by touching it, it floats in place.
+pub struct Pickup {
+    pub entity: Entity,
+    pub kind: PickupKind,
+    /// Set for `PickupKind::PowerUp`.
+    pub power_up: Option<PowerUpKind>,
+    /// Set for `PickupKind::Ability`.
+    pub ability: Option<Ability>,
+    pub position: Point2<f32>,
+
+    body_handle: BodyHandle,
+    collider_handle: ColliderHandle,
+    removal_queue: RemovalQueue,
+}
+
+impl Pickup {
+    pub fn new(kind: PickupKind, position: Point2<f32>, world: &mut World) -> Self {
+        let entity = world.entities.spawn(ObjectType::Pickup);
+
+        let body_handle = world.physics.add_body(BodyDesc::fixed().position(position));
+        let collider_handle = world.physics.add_sensor(
+            body_handle,
+            ColliderDesc::new(ColliderShape::Ball(PICKUP_RADIUS)),
+            entity,
+        ).expect("The body was just added");
+
+        Self {
+            entity,
+            kind,
+            power_up: None,
+            ability: None,
+            position,
+            body_handle,
+            collider_handle,
+            removal_queue: world.physics.removal_queue(),
+        }
+    }
+
+    pub fn power_up(power_up: PowerUpKind, position: Point2<f32>, world: &mut World) -> Self {
+        let mut pickup = Self::new(PickupKind::PowerUp, position, world);
+        pickup.power_up = Some(power_up);
+
+        pickup
+    }
+
+    /// Grants `ability` to whoever collects it for the rest of the level.
+    pub fn ability(ability: Ability, position: Point2<f32>, world: &mut World) -> Self {
+        let mut pickup = Self::new(PickupKind::Ability, position, world);
+        pickup.ability = Some(ability);
+
+        pickup
+    }
+
+    /// Makes a pickup collected since the snapshot again.
+    pub fn from_snapshot(snapshot: &PickupSnapshot, world: &mut World) -> Self {
+        let mut pickup = Self::new(snapshot.kind, snapshot.position.into(), world);
+        pickup.power_up = snapshot.power_up;
+        pickup.ability = snapshot.ability;
+
+        pickup
+    }
+
+    pub fn snapshot(&self) -> PickupSnapshot {
+        PickupSnapshot {
+            kind: self.kind,
+            power_up: self.power_up,
+            ability: self.ability,
+            position: self.position.into(),
+        }
+    }
+
+    pub fn collider_handle(&self) -> ColliderHandle {
+        self.collider_handle
+    }
+
+    pub fn color(&self) -> graphics::Color {
+        self.power_up
+            .map(PowerUpKind::color)
+            .unwrap_or_else(|| self.kind.color())
+    }
+
+    pub fn radius(&self) -> f32 {
+        PICKUP_RADIUS
+    }
+}
+
+impl Drop for Pickup {
+    fn drop(&mut self) {
+        self.removal_queue.remove_body(self.body_handle);
+    }
+}
+
+/// Gets told about every pickup that is collected, for sounds and effects.
+pub trait PickupHook {
+    fn collected(&mut self, ctx: &mut Context, kind: PickupKind, position: Point2<f32>);
+    fn update(&mut self, _dt: f32) {}
+    fn draw(&mut self, _ctx: &mut Context) {}
+}
+
+struct Particle {
+    position: Point2<f32>,
+    velocity: Vector2<f32>,
+    /// Seconds left, it fades out over `PARTICLE_LIFETIME`.
+    life: f32,
+    color: graphics::Color,
+}
+
+const PARTICLE_LIFETIME: f32 = 0.4;
+const PARTICLE_COUNT: usize = 8;
+const PARTICLE_SPEED: f32 = 60.;
+
+/// A burst of sparks in the pickup's color.
+#[derive(Default)]
+pub struct PickupParticles {
+    particles: Vec<Particle>,
+}
+
+impl PickupHook for PickupParticles {
+    fn collected(&mut self, _ctx: &mut Context, kind: PickupKind, position: Point2<f32>) {
+        self.particles.extend((0..PARTICLE_COUNT).map(|i| {
+            let angle = i as f32 / PARTICLE_COUNT as f32 * std::f32::consts::PI * 2.;
+
+            Particle {
+                position,
+                velocity: Vector2::new(angle.cos(), angle.sin()) * PARTICLE_SPEED,
+                life: PARTICLE_LIFETIME,
+                color: kind.color(),
+            }
+        }));
+    }
+
+    fn update(&mut self, dt: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.position += particle.velocity * dt;
+            particle.life -= dt;
+        }
+        self.particles.retain(|particle| particle.life > 0.);
+    }
+
+    fn draw(&mut self, ctx: &mut Context) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let mut builder = graphics::MeshBuilder::new();
+        for particle in self.particles.iter() {
+            let mut color = particle.color;
+            color.a = particle.life / PARTICLE_LIFETIME;
+
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(particle.position.x - 1.5, particle.position.y - 1.5, 3., 3.),
+                color,
+            );
+        }
+
+        let mesh = builder.build(ctx).unwrap();
+
+        graphics::draw(
+            ctx, 
+            &mesh, 
+            graphics::DrawParam::new()
+                .dest(util::point_to_old(Point2::new(100., 100.))),
+        )
+        .unwrap();
+    }
+}
+
+/// Plays `/coin.ogg`, `/gem.ogg`, `/power_up.ogg` and `/ability.ogg` from the resources.
+/// Kinds without a sound file are silent.
+pub struct PickupSounds {
+    sounds: Vec<(PickupKind, audio::Source)>,
+}
+
+impl PickupSounds {
+    pub fn new(ctx: &mut Context) -> Self {
+        let sounds = PickupKind::ALL
+            .iter()
+            .filter_map(|kind| {
+                let path = match kind {
+                    PickupKind::Coin => "/coin.ogg",
+                    PickupKind::Gem => "/gem.ogg",
+                    PickupKind::PowerUp => "/power_up.ogg",
+                    PickupKind::Ability => "/ability.ogg",
+                };
+
+                audio::Source::new(ctx, path).ok().map(|source| (*kind, source))
+            })
+            .collect();
+
+        Self {
+            sounds,
+        }
+    }
+}
+
+impl PickupHook for PickupSounds {
+    fn collected(&mut self, _ctx: &mut Context, kind: PickupKind, _position: Point2<f32>) {
+        if let Some((_, source)) = self.sounds.iter_mut().find(|(sound_kind, _)| *sound_kind == kind) {
+            if let Err(e) = source.play() {
+                log::warn!("Failed to play pickup sound: {}", e);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,17 +1,16 @@
-use crate::{physics::ObjectType, World};
+use crate::{entity::Entity, physics::{BodyDesc, BodyHandle, ColliderDesc, ColliderHandle, ColliderShape, ObjectType, RemovalQueue}, World};
 use nalgebra::{Point2, Vector2};
 
-use nphysics2d::object::{Ground, ColliderDesc, DefaultBodyHandle, DefaultColliderHandle,};
-
-use ncollide2d::shape::{Cuboid, ShapeHandle};
-
 pub struct Platform {
-    pub object_type: ObjectType,
+    pub entity: Entity,
     pub position: Point2<f32>,
-    pub shape: Cuboid<f32>,
+    pub shape: ColliderShape,
+    /// Radians, anything up to the max slope can be walked on.
+    pub rotation: f32,
 
-    collider_handle: DefaultColliderHandle,
-    body_handle: DefaultBodyHandle,
+    collider_handle: ColliderHandle,
+    body_handle: BodyHandle,
+    removal_queue: RemovalQueue,
 }
 
 impl Platform {
@@ -21,25 +20,45 @@ impl Platform {
         position: Point2<f32>,
         world: &mut World,
     ) -> Self {
-        let shape = Cuboid::new(size);
-        let shape_handle = ShapeHandle::new(shape.clone());
+        Self::sloped(size, translation, position, 0., world)
+    }
+
+    pub fn sloped(
+        size: Vector2<f32>,
+        translation: Vector2<f32>,
+        position: Point2<f32>,
+        rotation: f32,
+        world: &mut World,
+    ) -> Self {
+        let entity = world.entities.spawn(ObjectType::Platform);
+
+        let shape = ColliderShape::Cuboid(size);
         
-        let body_handle = world.physics.bodies.insert(Ground::new());
-        let collider_desc = ColliderDesc::new(shape_handle)
+        let body_handle = world.physics.add_body(BodyDesc::fixed());
+        let collider_desc = ColliderDesc::new(shape)
             .translation(translation)
-            .user_data(ObjectType::Platform);
+            .rotation(rotation);
 
         let collider_handle = world.physics.add_collider(
             body_handle, 
-            collider_desc
-        );
+            collider_desc,
+            entity,
+        ).expect("The body was just added");
 
         Self {
-            object_type: ObjectType::Platform,
+            entity,
             position,
-            shape: shape,
+            shape,
+            rotation,
             collider_handle,
             body_handle,
+            removal_queue: world.physics.removal_queue(),
         }
     }
+}
+
+impl Drop for Platform {
+    fn drop(&mut self) {
+        self.removal_queue.remove_body(self.body_handle);
+    }
 }
\ No newline at end of file
@@ -1,70 +1,335 @@
-use crate::{Context, world::World, physics::ObjectType, util, timer};
+use crate::{world::World, entity::Entity, power_up::ActivePowerUps, snapshot::{BodySnapshot, PlayerSnapshot, RopeSnapshot}, physics::{BodyDesc, BodyHandle, ColliderDesc, ColliderHandle, ColliderShape, ObjectType, ObjectTypeSet, QueryFilter, RemovalQueue, RopeHandle, WallSide}, util};
 extern crate nalgebra as na;
 use na::{Vector2, Point2};
+use serde::{Deserialize, Serialize};
 
-use ncollide2d::shape::{ ShapeHandle, Cuboid };
-use nphysics2d::object::{DefaultBodyHandle, DefaultColliderHandle, RigidBodyDesc, ColliderDesc};
-
-const MAX_VEL: f32 = 100.;
-const FALL_MULTIPLIER: f32 = 12.5;
-const LOW_JUMP_MULTIPLIER: f32 = 12.;
-const JUMP_POWER: f32 = 20.;
-
-#[derive(Default)]
+#[derive(PartialEq, Eq, Default, Debug, Clone, Copy)]
 pub struct PlayerInput {
     pub left: bool,
     pub right: bool,
+    pub up: bool,
+    pub down: bool,
     pub jump: bool,
+    pub dash: bool,
+    pub grapple: bool,
+}
+
+impl PlayerInput {
+    /// One bit per button, small enough to send every frame.
+    pub fn to_bits(self) -> u8 {
+        [self.left, self.right, self.up, self.down, self.jump, self.dash, self.grapple]
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, pressed)| bits | (*pressed as u8) << i)
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        let pressed = |i: u8| bits & 1 << i != 0;
+
+        Self {
+            left: pressed(0),
+            right: pressed(1),
+            up: pressed(2),
+            down: pressed(3),
+            jump: pressed(4),
+            dash: pressed(5),
+            grapple: pressed(6),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Ability {
+    DoubleJump,
+    AirDash,
+    Grapple,
+}
+
+/// Optional movement abilities, enabled by the level or granted by pickups.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Abilities {
+    pub double_jump: bool,
+    pub air_dash: bool,
+    pub grapple: bool,
 }
 
 pub struct Player {
     pub input: PlayerInput,
     pub position: Point2<f32>,
     pub velocity: Vector2<f32>,
-    body_handle: DefaultBodyHandle,
-    collider_handle: DefaultColliderHandle,
+    /// Where the player was when they last touched a checkpoint.
+    pub checkpoint: Option<Point2<f32>>,
+    entity: Entity,
+    body_handle: BodyHandle,
+    collider_handle: ColliderHandle,
+
+    pub abilities: Abilities,
+    pub power_ups: ActivePowerUps,
     
     has_jumped: bool,
-    last_on_ground: (bool, bool),
+    jump_was_pressed: bool,
+    time_since_grounded: f32,
+    time_since_jump_pressed: f32,
+    wall_side: Option<WallSide>,
+    wall_jump_lock: f32,
+    facing: f32,
+    can_double_jump: bool,
+    can_dash: bool,
+    dash_was_pressed: bool,
+    dash_direction: Point2<f32>,
+    dash_time: f32,
+    dash_cooldown: f32,
+    grapple_was_pressed: bool,
+    rope: Option<RopeHandle>,
+
+    removal_queue: RemovalQueue,
 }
 
 impl Player {
     pub fn new(world: &mut World) -> Self {
-        let rigid_body_desc = RigidBodyDesc::new()
-            .translation(Vector2::new(0., 0.))
+        Self::spawn_at(Point2::new(0., 0.), world)
+    }
+
+    pub fn spawn_at(position: Point2<f32>, world: &mut World) -> Self {
+        let entity = world.entities.spawn(ObjectType::Player);
+
+        let body_desc = BodyDesc::dynamic()
+            .position(position)
             .mass(10.2);
 
-        let body_handle = world.physics.add_rigid_body(rigid_body_desc);
+        let body_handle = world.physics.add_body(body_desc);
 
         let rad = 10.;
 
-        let shape_handle = ShapeHandle::new(Cuboid::new(Vector2::repeat(rad)));
-        let collider_desc = ColliderDesc::new(shape_handle)
-            .user_data(ObjectType::Player);
+        let collider_desc = ColliderDesc::new(ColliderShape::Cuboid(Vector2::repeat(rad)));
        
         let collider_handle = world.physics.add_collider(
             body_handle, 
             collider_desc,
-        );
+            entity,
+        ).expect("The body was just added");
         
         Self {
             input: Default::default(),
-            position: Point2::new(0., 0.),
+            position,
             velocity: Vector2::zeros(),
+            checkpoint: None,
+            entity,
             body_handle,
             collider_handle,
+            abilities: Default::default(),
+            power_ups: Default::default(),
             has_jumped: false,
-            last_on_ground: (true, true),
+            jump_was_pressed: false,
+            time_since_grounded: 0.,
+            time_since_jump_pressed: f32::INFINITY,
+            wall_side: None,
+            wall_jump_lock: 0.,
+            facing: 1.,
+            can_double_jump: false,
+            can_dash: false,
+            dash_was_pressed: false,
+            dash_direction: Point2::new(0., 0.),
+            dash_time: 0.,
+            dash_cooldown: 0.,
+            grapple_was_pressed: false,
+            rope: None,
+            removal_queue: world.physics.removal_queue(),
+        }
+    }
+
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn collider_handle(&self) -> ColliderHandle {
+        self.collider_handle
+    }
+
+    pub fn grant_ability(&mut self, ability: Ability) {
+        match ability {
+            Ability::DoubleJump => self.abilities.double_jump = true,
+            Ability::AirDash => self.abilities.air_dash = true,
+            Ability::Grapple => self.abilities.grapple = true,
+        }
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.dash_time > 0.
+    }
+
+    fn start_dash(&mut self, world: &mut World) {
+        let vertical = self.input.down as i32 as f32 - self.input.up as i32 as f32;
+        let horizontal = self.input.right as i32 as f32 - self.input.left as i32 as f32;
+        let horizontal = if horizontal == 0. && vertical == 0. {
+            self.facing
+        } else {
+            horizontal
+        };
+
+        let direction = Vector2::new(horizontal, vertical).normalize();
+        self.dash_direction = Point2::new(direction.x, direction.y);
+        self.dash_time = world.movement.dash_duration;
+        self.dash_cooldown = world.movement.dash_cooldown;
+        self.can_dash = false;
+
+        world.physics.set_gravity_enabled(self.body_handle, false);
+    }
+
+    pub fn snapshot(&self, world: &World) -> Option<PlayerSnapshot> {
+        let body = BodySnapshot {
+            position: world.physics.get_position(self.body_handle)?.into(),
+            velocity: world.physics.get_velocity(self.body_handle)?.into(),
+        };
+        let rope = self.rope.and_then(|rope| {
+            let (hook, point) = world.physics.rope_hook(rope)?;
+
+            Some(RopeSnapshot {
+                hook,
+                length: world.physics.rope_length(rope)?,
+                point: point.into(),
+            })
+        });
+
+        Some(PlayerSnapshot {
+            double_jump: self.abilities.double_jump,
+            air_dash: self.abilities.air_dash,
+            grapple: self.abilities.grapple,
+            has_jumped: self.has_jumped,
+            jump_was_pressed: self.jump_was_pressed,
+            time_since_grounded: self.time_since_grounded,
+            time_since_jump_pressed: self.time_since_jump_pressed,
+            wall_jump_lock: self.wall_jump_lock,
+            facing: self.facing,
+            can_double_jump: self.can_double_jump,
+            can_dash: self.can_dash,
+            dash_was_pressed: self.dash_was_pressed,
+            dash_time: self.dash_time,
+            dash_cooldown: self.dash_cooldown,
+            grapple_was_pressed: self.grapple_was_pressed,
+            checkpoint: self.checkpoint.map(Into::into),
+            dash_direction: self.dash_direction.into(),
+            body,
+            power_ups: self.power_ups.clone(),
+            rope,
+        })
+    }
+
+    /// The rope is hooked again where the snapshot had it, to the same collider.
+    pub fn restore(&mut self, snapshot: &PlayerSnapshot, world: &mut World) {
+        if let Some(rope) = self.rope.take() {
+            world.physics.detach_rope(rope);
+        }
+
+        self.abilities = Abilities {
+            double_jump: snapshot.double_jump,
+            air_dash: snapshot.air_dash,
+            grapple: snapshot.grapple,
+        };
+        self.has_jumped = snapshot.has_jumped;
+        self.jump_was_pressed = snapshot.jump_was_pressed;
+        self.time_since_grounded = snapshot.time_since_grounded;
+        self.time_since_jump_pressed = snapshot.time_since_jump_pressed;
+        self.wall_jump_lock = snapshot.wall_jump_lock;
+        self.facing = snapshot.facing;
+        self.can_double_jump = snapshot.can_double_jump;
+        self.can_dash = snapshot.can_dash;
+        self.dash_was_pressed = snapshot.dash_was_pressed;
+        self.dash_direction = snapshot.dash_direction.into();
+        self.dash_time = snapshot.dash_time;
+        self.dash_cooldown = snapshot.dash_cooldown;
+        self.grapple_was_pressed = snapshot.grapple_was_pressed;
+        self.checkpoint = snapshot.checkpoint.map(Into::into);
+        self.power_ups = snapshot.power_ups.clone();
+        // The wall is found again on the next update
+        self.wall_side = None;
+
+        self.position = snapshot.body.position.into();
+        world.physics.set_position(self.body_handle, self.position);
+        world.physics.set_velocity(self.body_handle, snapshot.body.velocity.into());
+        world.physics.set_gravity_enabled(self.body_handle, !self.is_dashing());
+
+        self.rope = snapshot.rope.and_then(|rope| {
+            let handle = world.physics.attach_rope(self.body_handle, rope.hook, rope.point.into())?;
+            world.physics.set_rope_length(handle, rope.length);
+
+            Some(handle)
+        });
+    }
+
+    /// Puts the player back at `position` at rest, with nothing but the abilities,
+    /// power-ups and checkpoint carried over.
+    pub fn respawn(&mut self, position: Point2<f32>, world: &mut World) {
+        if let Some(rope) = self.rope.take() {
+            world.physics.detach_rope(rope);
+        }
+
+        self.has_jumped = false;
+        self.time_since_grounded = 0.;
+        self.time_since_jump_pressed = f32::INFINITY;
+        self.wall_side = None;
+        self.wall_jump_lock = 0.;
+        self.dash_time = 0.;
+        self.dash_cooldown = 0.;
+
+        self.position = position;
+        world.physics.set_position(self.body_handle, position);
+        world.physics.set_velocity(self.body_handle, Point2::new(0., 0.));
+        world.physics.set_gravity_enabled(self.body_handle, true);
+    }
+
+    pub fn is_grappling(&self) -> bool {
+        self.rope.is_some()
+    }
+
+    /// Aims like the dash, but up and forward when no direction is held.
+    fn fire_grapple(&self, position: Point2<f32>, down: f32, world: &mut World) -> Option<RopeHandle> {
+        let vertical = self.input.down as i32 as f32 - self.input.up as i32 as f32;
+        let horizontal = self.input.right as i32 as f32 - self.input.left as i32 as f32;
+        let aim = if horizontal == 0. && vertical == 0. {
+            Vector2::new(self.facing, -down)
+        } else {
+            Vector2::new(horizontal, vertical)
+        };
+
+        let filter = QueryFilter::excluding(self.collider_handle)
+            .with_object_types(ObjectTypeSet::of(&[ObjectType::Platform]));
+        let hit = world.physics.raycast(position, aim, world.movement.grapple_range, &filter)?;
+
+        world.physics.attach_rope(self.body_handle, hit.collider, hit.point)
+    }
+
+    /// Up and down reel the rope in and out, left and right pump the swing.
+    fn swing(&mut self, rope: RopeHandle, velocity: Point2<f32>, dt: f32, world: &mut World) {
+        let profile = world.movement;
+
+        if let Some(length) = world.physics.rope_length(rope) {
+            let reel = self.input.down as i32 as f32 - self.input.up as i32 as f32;
+            let length = (length + reel * profile.grapple_reel_speed * dt)
+                .max(profile.grapple_min_length)
+                .min(profile.grapple_range);
+            world.physics.set_rope_length(rope, length);
         }
+
+        let horizontal = self.input.right as i32 as f32 - self.input.left as i32 as f32;
+        let push = Point2::new(horizontal * profile.swing_acceleration * dt, 0.);
+        world.physics.set_velocity(self.body_handle, util::add(velocity, push));
     }
 
-    pub fn update(&mut self, ctx: &Context, world: &mut World) {
-        let dt: f32 = timer::delta(ctx).as_secs_f32();
+    pub fn update(&mut self, dt: f32, world: &mut World) {
+        let profile = world.movement;
+        self.power_ups.update(dt);
+        let gravity_scale = self.power_ups.gravity_scale(&world.power_ups);
+        let jump_scale = self.power_ups.jump_scale(&world.power_ups);
 
         let direction = Vector2::new(
             self.input.right as i32 as f32 - self.input.left as i32 as f32,
             0.0,
         );
+        if direction.x != 0. {
+            self.facing = direction.x;
+        }
         
         let movement_direction = if direction.x > 0. {    
             Point2::new(1., 0.)
@@ -74,46 +339,192 @@ impl Player {
             Point2::new(0., 0.)
         };
 
-        let mut velocity: Point2<f32> = world.physics.get_velocity(self.body_handle);
-        velocity[0] = 0.;   // stop the velocity.x
-        
-        let is_grounded = self.last_on_ground.0 || self.last_on_ground.1;
+        // Nothing to move once the body is gone
+        let (mut velocity, position) = match (
+            world.physics.get_velocity(self.body_handle),
+            world.physics.get_position(self.body_handle),
+        ) {
+            (Some(velocity), Some(position)) => (velocity, position),
+            _ => return,
+        };
+
+        let wall_jump_locked = self.wall_jump_lock > 0.;
+        if wall_jump_locked {
+            self.wall_jump_lock -= dt;
+        }
+
+        let gravity = world.physics.gravity_at(position);
+        // Movement assumes vertical gravity, `down` flips it inside reversed gravity zones
+        let down = if gravity.y < 0. { -1. } else { 1. };
+        let gravity_strength = gravity.y.abs() * gravity_scale;
 
-        let max_vel = if is_grounded {
-            MAX_VEL
+        let on_ground = world.physics.ground_check(self.collider_handle, ObjectTypeSet::of(&[ObjectType::Platform]));
+        self.wall_side = if on_ground {
+            None
         } else {
-            MAX_VEL - 20.
+            world.physics.wall_check(self.collider_handle)
         };
+        if on_ground {
+            self.time_since_grounded = 0.;
+            if velocity.y * down >= 0. {
+                self.has_jumped = false;
+            }
+            self.can_double_jump = true;
+            self.can_dash = true;
+        } else {
+            self.time_since_grounded += dt;
+        }
+
+        // Only the press itself starts the buffer, holding the key does not
+        if self.input.jump && !self.jump_was_pressed {
+            self.time_since_jump_pressed = 0.;
+        } else {
+            self.time_since_jump_pressed += dt;
+        }
+        self.jump_was_pressed = self.input.jump;
+
+        // The rope goes away with the platform it hooked
+        if let Some(rope) = self.rope {
+            if world.physics.rope_length(rope).is_none() {
+                self.rope = None;
+            }
+        }
+
+        let grapple_pressed = self.input.grapple && !self.grapple_was_pressed;
+        self.grapple_was_pressed = self.input.grapple;
+
+        if grapple_pressed && self.abilities.grapple && !self.is_dashing() {
+            match self.rope.take() {
+                // Letting go keeps the momentum from the swing
+                Some(rope) => { world.physics.detach_rope(rope); },
+                None => self.rope = self.fire_grapple(position, down, world),
+            }
+        }
+
+        if let Some(rope) = self.rope {
+            self.swing(rope, velocity, dt, world);
+            self.position = position;
+            return;
+        }
 
-        world.physics.set_velocity(self.body_handle, util::add(velocity, movement_direction * max_vel));
-        self.position = world.physics.get_position(self.body_handle);
+        let dash_pressed = self.input.dash && !self.dash_was_pressed;
+        self.dash_was_pressed = self.input.dash;
+        self.dash_cooldown -= dt;
 
-        let on_ground = world.physics.ground_check(self.collider_handle, ObjectType::Player);
-        let in_air = !(on_ground || self.last_on_ground.0 || !self.has_jumped);
+        if dash_pressed && self.abilities.air_dash && self.can_dash && !on_ground && self.dash_cooldown <= 0. {
+            self.start_dash(world);
+        }
+
+        if self.is_dashing() {
+            self.dash_time -= dt;
+            if self.is_dashing() {
+                world.physics.set_velocity(self.body_handle, self.dash_direction * profile.dash_speed);
+                self.position = world.physics.get_position(self.body_handle).unwrap_or(position);
+                return;
+            }
+
+            world.physics.set_gravity_enabled(self.body_handle, true);
+            velocity = Point2::new(0., 0.);
+        }
+
+        let is_grounded = self.time_since_grounded <= profile.coyote_time;
+
+        let (max_vel, acceleration, deceleration) = if on_ground {
+            (profile.max_vel, profile.ground_acceleration, profile.ground_deceleration)
+        } else {
+            (profile.air_max_vel, profile.air_acceleration, profile.air_deceleration)
+        };
+
+        // Horizontal input is ignored while the kick from a wall jump plays out
+        if !wall_jump_locked {
+            let steering = movement_direction.x * max_vel;
+            let rate = if steering == 0. {
+                deceleration
+            } else if steering * velocity.x < 0. {
+                acceleration * profile.turn_around_multiplier
+            } else {
+                acceleration
+            };
+            let drift = world.physics.force_at(position).x * profile.wind_carry;
+            velocity.x = util::approach(velocity.x, steering + drift, rate * dt);
+        }
 
-        let gravity = world.physics.get_gravity();
-        let up = Point2::new(0., 1.);
+        world.physics.set_velocity(self.body_handle, velocity);
+        self.position = world.physics.get_position(self.body_handle).unwrap_or(position);
 
-        if (is_grounded || !self.has_jumped) && self.input.jump {
+        let jump_buffered = self.time_since_jump_pressed <= profile.jump_buffer_time;
+        if is_grounded && !self.has_jumped && jump_buffered {
             self.has_jumped = true;
-            let jump_vector = up * -JUMP_POWER;
-            velocity[0] = 0.;
+            // Consume both windows so a single press can't fire twice
+            self.time_since_grounded = f32::INFINITY;
+            self.time_since_jump_pressed = f32::INFINITY;
+
+            let jump_vector = Point2::new(0., -down * profile.jump_power * jump_scale);
             world.physics.set_velocity(self.body_handle, util::add(velocity, jump_vector));
-            println!("JUMP {}", self.input.jump);
+        } else if let Some(wall_side) = self.wall_side.filter(|_| jump_buffered) {
+            self.has_jumped = true;
+            self.time_since_jump_pressed = f32::INFINITY;
+            self.wall_jump_lock = profile.wall_jump_lock_time;
+
+            let away = match wall_side {
+                WallSide::Left => 1.,
+                WallSide::Right => -1.,
+            };
+            let jump_velocity = Point2::new(away * profile.wall_jump_kick, -down * profile.wall_jump_power);
+            world.physics.set_velocity(self.body_handle, jump_velocity);
+        } else if self.abilities.double_jump && self.can_double_jump && jump_buffered {
+            self.can_double_jump = false;
+            self.time_since_jump_pressed = f32::INFINITY;
+
+            let jump_velocity = Point2::new(velocity.x, -down * profile.double_jump_power * jump_scale);
+            world.physics.set_velocity(self.body_handle, jump_velocity);
         }
 
+        // The physics world pulls every body the same, take back what a lighter player doesn't feel
+        let velocity: Point2<f32> = world.physics.get_velocity(self.body_handle).unwrap_or(velocity);
+        let velocity = if gravity_scale != 1. {
+            let lift: Point2<f32> = Point2::new(0., down * gravity.y.abs() * (gravity_scale - 1.) * dt);
+            world.physics.set_velocity(self.body_handle, util::add(velocity, lift));
+            util::add(velocity, lift)
+        } else {
+            velocity
+        };
+
         // Better jumping
-        let velocity: Point2<f32> = world.physics.get_velocity(self.body_handle);
-        if velocity.y > 0.0 {
-            let vel: Point2<f32> =  up * gravity * (FALL_MULTIPLIER - 1.) * dt;
+        let fall_speed = velocity.y * down;
+        if fall_speed > 0.0 {
+            let vel: Point2<f32> = Point2::new(0., down * gravity_strength * (profile.fall_multiplier - 1.) * dt);
             world.physics.set_velocity(self.body_handle, util::add(velocity, vel));
-        } else if velocity.y < 0.3 && !self.input.jump {
-            let vel: Point2<f32> = up * gravity * (LOW_JUMP_MULTIPLIER - 1.) * dt;
+        } else if fall_speed < 0.3 && !self.input.jump {
+            let vel: Point2<f32> = Point2::new(0., down * gravity_strength * (profile.low_jump_multiplier - 1.) * dt);
             world.physics.set_velocity(self.body_handle, util::add(velocity, vel));
         }
 
-        
-        self.last_on_ground.0 = self.last_on_ground.1;
-        self.last_on_ground.1 = on_ground;
+        // Wall slide
+        let velocity: Point2<f32> = world.physics.get_velocity(self.body_handle).unwrap_or(velocity);
+        if self.wall_side.is_some() && velocity.y * down > profile.wall_slide_speed {
+            world.physics.set_velocity(self.body_handle, Point2::new(velocity.x, down * profile.wall_slide_speed));
+        }
     } 
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.removal_queue.remove_body(self.body_handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_survive_being_sent_as_bits() {
+        for bits in 0..1 << 7 {
+            let input = PlayerInput::from_bits(bits);
+
+            assert_eq!(input.to_bits(), bits);
+            assert_eq!(PlayerInput::from_bits(input.to_bits()), input);
+        }
+    }
 }
\ No newline at end of file
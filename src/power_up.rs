@@ -0,0 +1,329 @@
+use std::{cmp::Ordering, fs, path::Path};
+
+use ggez::{graphics, GameResult};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    /// Stops the lava from rising.
+    LavaFreeze,
+    /// Survives touching the lava.
+    HeatShield,
+    /// Lighter gravity on the player.
+    Feather,
+    /// Higher jumps.
+    Springs,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::LavaFreeze,
+        PowerUpKind::HeatShield,
+        PowerUpKind::Feather,
+        PowerUpKind::Springs,
+    ];
+
+    pub fn color(self) -> graphics::Color {
+        match self {
+            PowerUpKind::LavaFreeze => graphics::Color::from((170, 200, 255, 255)),
+            PowerUpKind::HeatShield => graphics::Color::from((255, 140, 40, 255)),
+            PowerUpKind::Feather => graphics::Color::from((240, 240, 240, 255)),
+            PowerUpKind::Springs => graphics::Color::from((120, 230, 90, 255)),
+        }
+    }
+}
+
+/// What collecting a power-up does while one of the same kind is still running.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Stacking {
+    /// Starts the duration over.
+    Refresh,
+    /// Adds the duration to what is left.
+    Extend,
+    /// Lets the running one play out.
+    Ignore,
+}
+
+impl Stacking {
+    /// Seconds left after collecting one with `remaining` seconds still to go.
+    pub fn apply(self, remaining: f32, duration: f32) -> f32 {
+        if remaining <= 0. {
+            return duration;
+        }
+
+        match self {
+            Stacking::Refresh => remaining.max(duration),
+            Stacking::Extend => remaining + duration,
+            Stacking::Ignore => remaining,
+        }
+    }
+}
+
+/// Everything one power-up does, neutral values leave that part alone.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PowerUpEffect {
+    /// Seconds the effect lasts.
+    pub duration: f32,
+    pub stacking: Stacking,
+    /// Multiplies the gravity the player falls with.
+    pub gravity_scale: f32,
+    /// Multiplies the power of ground and double jumps.
+    pub jump_scale: f32,
+    /// Lava touches survived before the effect runs out.
+    pub shield_charges: u32,
+    pub freezes_lava: bool,
+}
+
+impl Default for PowerUpEffect {
+    fn default() -> Self {
+        Self {
+            duration: 5.,
+            stacking: Stacking::Refresh,
+            gravity_scale: 1.,
+            jump_scale: 1.,
+            shield_charges: 0,
+            freezes_lava: false,
+        }
+    }
+}
+
+impl PowerUpEffect {
+    /// Lava freezes run on the lava, everything else on the player who collected it.
+    pub fn affects_player(&self) -> bool {
+        self.gravity_scale != 1. || self.jump_scale != 1. || self.shield_charges > 0
+    }
+}
+
+/// The effect of every power-up, tuned from `powerups.toml`.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PowerUpTable {
+    pub lava_freeze: PowerUpEffect,
+    pub heat_shield: PowerUpEffect,
+    pub feather: PowerUpEffect,
+    pub springs: PowerUpEffect,
+}
+
+impl Default for PowerUpTable {
+    fn default() -> Self {
+        Self {
+            lava_freeze: PowerUpEffect {
+                duration: 3.,
+                stacking: Stacking::Extend,
+                freezes_lava: true,
+                ..PowerUpEffect::default()
+            },
+            heat_shield: PowerUpEffect {
+                duration: 15.,
+                shield_charges: 1,
+                ..PowerUpEffect::default()
+            },
+            feather: PowerUpEffect {
+                duration: 6.,
+                gravity_scale: 0.5,
+                ..PowerUpEffect::default()
+            },
+            springs: PowerUpEffect {
+                duration: 6.,
+                jump_scale: 1.4,
+                ..PowerUpEffect::default()
+            },
+        }
+    }
+}
+
+impl PowerUpTable {
+    pub fn get(&self, kind: PowerUpKind) -> &PowerUpEffect {
+        match kind {
+            PowerUpKind::LavaFreeze => &self.lava_freeze,
+            PowerUpKind::HeatShield => &self.heat_shield,
+            PowerUpKind::Feather => &self.feather,
+            PowerUpKind::Springs => &self.springs,
+        }
+    }
+
+    /// Keys missing from the file keep their default, also inside each power-up.
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let contents = fs::read_to_string(path)?;
+        let overrides: toml::value::Table = toml::from_str(&contents)?;
+
+        let mut values = toml::Value::try_from(Self::default())?;
+        if let toml::Value::Table(table) = &mut values {
+            for (key, value) in overrides {
+                match (table.get_mut(&key), value) {
+                    (Some(toml::Value::Table(effect)), toml::Value::Table(effect_overrides)) => {
+                        effect.extend(effect_overrides);
+                    },
+                    (_, value) => {
+                        table.insert(key, value);
+                    },
+                }
+            }
+        }
+
+        Ok(values.try_into()?)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct PowerUpTimer {
+    pub kind: PowerUpKind,
+    /// Seconds left.
+    pub remaining: f32,
+    /// Lava touches this one still survives, they run out with it.
+    #[serde(default)]
+    pub shield_charges: u32,
+}
+
+/// The power-ups running on one player.
+#[derive(Serialize, Deserialize, PartialEq, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ActivePowerUps {
+    pub timers: Vec<PowerUpTimer>,
+}
+
+impl ActivePowerUps {
+    pub fn add(&mut self, kind: PowerUpKind, effect: &PowerUpEffect) {
+        match self.timers.iter_mut().find(|timer| timer.kind == kind) {
+            Some(timer) => {
+                timer.remaining = effect.stacking.apply(timer.remaining, effect.duration);
+                timer.shield_charges = timer.shield_charges.max(effect.shield_charges);
+            },
+            None => self.timers.push(PowerUpTimer {
+                kind,
+                remaining: effect.duration,
+                shield_charges: effect.shield_charges,
+            }),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for timer in self.timers.iter_mut() {
+            timer.remaining -= dt;
+        }
+        self.timers.retain(|timer| timer.remaining > 0.);
+    }
+
+    /// Spends a shield charge from the power-up that runs out first, false if none
+    /// has any left. A power-up is over once its last charge is spent.
+    pub fn absorb_lava(&mut self) -> bool {
+        let index = self.timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.shield_charges > 0)
+            .min_by(|(_, a), (_, b)| a.remaining.partial_cmp(&b.remaining).unwrap_or(Ordering::Equal))
+            .map(|(index, _)| index);
+
+        let index = match index {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.timers[index].shield_charges -= 1;
+        if self.timers[index].shield_charges == 0 {
+            self.timers.remove(index);
+        }
+
+        true
+    }
+
+    pub fn gravity_scale(&self, table: &PowerUpTable) -> f32 {
+        self.timers
+            .iter()
+            .map(|timer| table.get(timer.kind).gravity_scale)
+            .product()
+    }
+
+    pub fn jump_scale(&self, table: &PowerUpTable) -> f32 {
+        self.timers
+            .iter()
+            .map(|timer| table.get(timer.kind).jump_scale)
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_starts_the_duration_over_unless_more_is_left() {
+        assert_eq!(Stacking::Refresh.apply(2., 5.), 5.);
+        assert_eq!(Stacking::Refresh.apply(7., 5.), 7.);
+    }
+
+    #[test]
+    fn extend_adds_the_duration_to_what_is_left() {
+        assert_eq!(Stacking::Extend.apply(2., 5.), 7.);
+    }
+
+    #[test]
+    fn ignore_lets_the_running_one_play_out() {
+        assert_eq!(Stacking::Ignore.apply(2., 5.), 2.);
+    }
+
+    #[test]
+    fn every_stacking_starts_over_once_the_last_one_ran_out() {
+        for stacking in [Stacking::Refresh, Stacking::Extend, Stacking::Ignore].iter() {
+            assert_eq!(stacking.apply(0., 5.), 5.);
+        }
+    }
+
+    #[test]
+    fn collecting_a_running_power_up_stacks_on_its_timer() {
+        let table = PowerUpTable::default();
+        let mut power_ups = ActivePowerUps::default();
+
+        power_ups.add(PowerUpKind::LavaFreeze, &table.lava_freeze);
+        power_ups.update(1.);
+        power_ups.add(PowerUpKind::LavaFreeze, &table.lava_freeze);
+
+        assert_eq!(power_ups.timers.len(), 1);
+        assert_eq!(power_ups.timers[0].remaining, 2. * table.lava_freeze.duration - 1.);
+    }
+
+    #[test]
+    fn shield_charges_run_out_with_their_timer() {
+        let table = PowerUpTable::default();
+        let mut power_ups = ActivePowerUps::default();
+
+        power_ups.add(PowerUpKind::HeatShield, &table.heat_shield);
+        power_ups.update(table.heat_shield.duration + 1.);
+
+        assert!(power_ups.timers.is_empty());
+        assert!(!power_ups.absorb_lava());
+    }
+
+    #[test]
+    fn the_last_charge_ends_the_power_up() {
+        let table = PowerUpTable::default();
+        let mut power_ups = ActivePowerUps::default();
+
+        power_ups.add(PowerUpKind::HeatShield, &table.heat_shield);
+
+        assert!(power_ups.absorb_lava());
+        assert!(power_ups.timers.is_empty());
+        assert!(!power_ups.absorb_lava());
+    }
+
+    #[test]
+    fn a_partial_override_keeps_the_other_defaults() {
+        let path = std::env::temp_dir().join(format!("powerups-{}.toml", std::process::id()));
+        fs::write(&path, "[feather]\nduration = 9.0\n").unwrap();
+
+        let table = PowerUpTable::load(&path);
+        fs::remove_file(&path).unwrap();
+        let table = table.unwrap();
+
+        let defaults = PowerUpTable::default();
+        assert_eq!(table.feather.duration, 9.);
+        assert_eq!(table.feather.gravity_scale, defaults.feather.gravity_scale);
+        assert_eq!(table.feather.stacking, defaults.feather.stacking);
+        assert_eq!(table.springs, defaults.springs);
+        assert_eq!(table.heat_shield, defaults.heat_shield);
+        assert_eq!(table.lava_freeze, defaults.lava_freeze);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,367 @@
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::physics::PhysicsKind;
+
+/// Bumped whenever the format changes, older files go through `migrate`.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Records of every run on one level.
+#[derive(Serialize, Deserialize, PartialEq, Default, Debug, Clone)]
+#[serde(default)]
+pub struct LevelStats {
+    pub runs: u32,
+    /// Longest anyone stayed out of the lava, in seconds.
+    pub best_time: f32,
+    /// Highest anyone got above the floor, in pixels.
+    pub max_height: f32,
+    /// Most coins collected in one run.
+    pub most_coins: u32,
+}
+
+/// How one run on a level went.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Run {
+    pub time: f32,
+    pub height: f32,
+    pub coins: u32,
+}
+
+/// The attempt at one day's challenge that counts.
+#[derive(Serialize, Deserialize, PartialEq, Default, Debug, Clone)]
+#[serde(default)]
+pub struct DailyRecord {
+    pub seed: u32,
+    /// Stays false when the game closes during the attempt, which spends it all the same.
+    pub finished: bool,
+    pub time: f32,
+    pub height: f32,
+    pub coins: u32,
+    /// Of the inputs, the replay itself is in `Replay::path`.
+    pub replay_hash: String,
+}
+
+/// Everything kept between launches, in `save.toml` in the user data directory.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    /// Every lava touch a heat shield didn't save, respawns included.
+    pub total_deaths: u32,
+    /// By level name.
+    pub levels: BTreeMap<String, LevelStats>,
+    /// Oldest first.
+    pub daily: Vec<DailyRecord>,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            total_deaths: 0,
+            levels: BTreeMap::new(),
+            daily: Vec::new(),
+        }
+    }
+}
+
+impl SaveData {
+    pub fn path(ctx: &Context) -> PathBuf {
+        filesystem::user_data_dir(ctx).join("save.toml")
+    }
+
+    /// Starts over from the defaults when there is no save yet. A file that can't
+    /// be read is moved aside to a `save.bak-<time>.toml` of its own first, so the
+    /// next save doesn't overwrite what could still be recovered by hand. A save from
+    /// a newer version of the game is left where it is, `save` won't replace it.
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match Self::read(path) {
+            Ok(save) => save,
+            Err(e) if Self::is_newer(path) => {
+                log::error!("Failed to load save, it is from a newer version of the game: {}", e);
+
+                Self::default()
+            },
+            Err(e) => {
+                let backup = Self::backup_path(path);
+                log::warn!("Failed to load save, backing it up to {}: {}", backup.display(), e);
+                if let Err(e) = fs::rename(path, &backup) {
+                    log::error!("Failed to back up save: {}", e);
+                }
+
+                Self::default()
+            },
+        }
+    }
+
+    /// Whether the save at `path` was written with a version past `SAVE_VERSION`.
+    fn is_newer(path: &Path) -> bool {
+        let version = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+            .and_then(|values| values.get("version").and_then(toml::Value::as_integer));
+
+        version.map(|version| version > i64::from(SAVE_VERSION)).unwrap_or(false)
+    }
+
+    /// Stamped with the time, and counted up if a backup was already made that second.
+    fn backup_path(path: &Path) -> PathBuf {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0);
+
+        (0..)
+            .map(|count| match count {
+                0 => path.with_extension(format!("bak-{}.toml", time)),
+                count => path.with_extension(format!("bak-{}-{}.toml", time, count)),
+            })
+            .find(|backup| !backup.exists())
+            .unwrap()
+    }
+
+    fn read(path: &Path) -> GameResult<Self> {
+        let contents = fs::read_to_string(path)?;
+        let values: toml::Value = toml::from_str(&contents)?;
+
+        let version = match values.get("version").and_then(toml::Value::as_integer) {
+            Some(version) if (0..=i64::from(SAVE_VERSION)).contains(&version) => version as u32,
+            Some(version) => return Err(GameError::ConfigError(format!("Unsupported save version {}", version))),
+            None => return Err(GameError::ConfigError("Save has no version".to_string())),
+        };
+
+        Ok(Self::migrate(values, version)?.try_into()?)
+    }
+
+    /// Brings a save written with `version` up to `SAVE_VERSION`, one version at a time.
+    fn migrate(values: toml::Value, version: u32) -> GameResult<toml::Value> {
+        match version {
+            SAVE_VERSION => Ok(values),
+            _ => Err(GameError::ConfigError(format!("No migration from save version {}", version))),
+        }
+    }
+
+    /// Writes next to the save first, so a crash halfway through leaves the old one intact.
+    /// Fails instead of replacing a save from a newer version of the game.
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if Self::is_newer(path) {
+            return Err(GameError::ConfigError(format!(
+                "{} is from a newer version of the game, not overwriting it",
+                path.display(),
+            )));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, toml::to_string(self)?)?;
+        fs::rename(&temporary, path)?;
+
+        Ok(())
+    }
+
+    pub fn record_run(&mut self, level: &str, run: Run) {
+        let stats = self.levels.entry(level.to_string()).or_default();
+        stats.runs += 1;
+        stats.best_time = stats.best_time.max(run.time);
+        stats.max_height = stats.max_height.max(run.height);
+        stats.most_coins = stats.most_coins.max(run.coins);
+    }
+
+    pub fn daily(&self, seed: u32) -> Option<&DailyRecord> {
+        self.daily.iter().find(|record| record.seed == seed)
+    }
+
+    /// Spends the attempt at `seed`, false if it was already spent.
+    pub fn start_daily(&mut self, seed: u32) -> bool {
+        if self.daily(seed).is_some() {
+            return false;
+        }
+
+        self.daily.push(DailyRecord {
+            seed,
+            ..DailyRecord::default()
+        });
+
+        true
+    }
+
+    pub fn finish_daily(&mut self, seed: u32, run: Run, replay_hash: String) {
+        if let Some(record) = self.daily.iter_mut().find(|record| record.seed == seed) {
+            *record = DailyRecord {
+                seed,
+                finished: true,
+                time: run.time,
+                height: run.height,
+                coins: run.coins,
+                replay_hash,
+            };
+        }
+    }
+}
+
+/// The inputs of a run, enough to play it back on the same seed to check a result.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Replay {
+    pub seed: u32,
+    pub replay_hash: String,
+    /// What the run claims, playing the inputs back has to end the same.
+    #[serde(default)]
+    pub time: f32,
+    #[serde(default)]
+    pub height: f32,
+    /// Backends don't simulate alike, the replay is played back on the one it was played on.
+    #[serde(default)]
+    pub physics: PhysicsKind,
+    /// `PlayerInput::to_bits` of every frame in hex, each frame is `TIME_STEP` long.
+    pub inputs: String,
+}
+
+impl Replay {
+    pub fn new(seed: u32, replay_hash: String, run: Run, physics: PhysicsKind, inputs: &[u8]) -> Self {
+        Self {
+            seed,
+            replay_hash,
+            time: run.time,
+            height: run.height,
+            physics,
+            inputs: inputs.iter().map(|bits| format!("{:02x}", bits)).collect(),
+        }
+    }
+
+    pub fn path(ctx: &Context, seed: u32) -> PathBuf {
+        filesystem::user_data_dir(ctx)
+            .join("replays")
+            .join(format!("daily-{}.toml", seed))
+    }
+
+    /// `None` if the inputs aren't valid hex.
+    pub fn inputs(&self) -> Option<Vec<u8>> {
+        (0..self.inputs.len())
+            .step_by(2)
+            .map(|i| self.inputs.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(toml::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory of its own for each test, tests run in parallel.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("save-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("save.bak-"))
+            .collect()
+    }
+
+    #[test]
+    fn a_corrupt_save_is_backed_up_and_starts_over() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("save.toml");
+        fs::write(&path, "version = [").unwrap();
+
+        assert_eq!(SaveData::load(&path), SaveData::default());
+        assert!(!path.exists());
+
+        let backups = backups(&dir);
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].to_string_lossy().ends_with(".toml"));
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "version = [");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backups_in_the_same_second_get_their_own_names() {
+        let dir = temp_dir("backups");
+        let path = dir.join("save.toml");
+
+        fs::write(&path, "first").unwrap();
+        SaveData::load(&path);
+        fs::write(&path, "second").unwrap();
+        SaveData::load(&path);
+
+        let mut contents: Vec<String> = backups(&dir)
+            .iter()
+            .map(|backup| fs::read_to_string(backup).unwrap())
+            .collect();
+        contents.sort();
+        assert_eq!(contents, vec!{"first".to_string(), "second".to_string()});
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_save_from_a_newer_version_is_left_alone() {
+        let dir = temp_dir("newer");
+        let path = dir.join("save.toml");
+        let newer = format!("version = {}\ntotal_deaths = 3\n", SAVE_VERSION + 1);
+        fs::write(&path, &newer).unwrap();
+
+        let save = SaveData::load(&path);
+        assert_eq!(save, SaveData::default());
+        assert!(backups(&dir).is_empty());
+
+        assert!(save.save(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn records_survive_a_round_trip() {
+        let dir = temp_dir("round_trip");
+        let path = dir.join("save.toml");
+
+        let mut save = SaveData {
+            total_deaths: 4,
+            ..SaveData::default()
+        };
+        save.record_run("Lava floor", Run { time: 12.5, height: 140., coins: 3 });
+        save.start_daily(20_261_019);
+        save.finish_daily(20_261_019, Run { time: 30., height: 250., coins: 5 }, "abc123".to_string());
+        save.start_daily(20_261_020);
+        save.save(&path).unwrap();
+
+        let loaded = SaveData::load(&path);
+        assert_eq!(loaded.levels, save.levels);
+        assert_eq!(loaded.daily, save.daily);
+        assert_eq!(loaded, save);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,201 @@
+use crate::{graphics, Context, Scene, World, KeyCode, pickup::PickupKind, leaderboard::LeaderboardEvent, score::Score, util, menu};
+extern crate nalgebra as na;
+use na::Point2;
+
+/// Top runs shown from the leaderboard.
+const LEADERBOARD_ENTRIES: usize = 5;
+
+/// Shows how a level went, with what was collected out of what it held.
+pub struct GameOverScene {
+    font: graphics::Font,
+    title_text: graphics::Text,
+    /// One line per pickup kind and the points, then any notes.
+    lines: Vec<graphics::Text>,
+    /// Empty unless the run was ranked.
+    leaderboard_lines: Vec<graphics::Text>,
+    continue_text: graphics::Text,
+    /// Seed whose top runs still have to be asked for.
+    leaderboard_seed: Option<u32>,
+
+    is_done: bool,
+}
+
+impl GameOverScene {
+    /// Only the title, the builders below add what there is to show.
+    pub fn new(ctx: &mut Context, title: &str) -> Self {
+        let font = graphics::Font::new(ctx, "/DejaVuSerif.ttf").unwrap();
+        let title_text_fragment = graphics::TextFragment::new(title)
+            .color(graphics::Color::from((192, 128, 64, 255)))
+            .font(font)
+            .scale(graphics::Scale::uniform(56.0));
+
+        Self {
+            font,
+            title_text: graphics::Text::new(title_text_fragment),
+            lines: Vec::new(),
+            leaderboard_lines: Vec::new(),
+            continue_text: Self::line(font, "Space to continue".to_string()),
+            leaderboard_seed: None,
+            is_done: false,
+        }
+    }
+
+    /// What was collected out of what the level held.
+    pub fn with_score(self, score: Score, totals: Score) -> Self {
+        let mut lines: Vec<String> = PickupKind::ALL
+            .iter()
+            .map(|kind| {
+                let name = match kind {
+                    PickupKind::Coin => "Coins",
+                    PickupKind::Gem => "Gems",
+                    PickupKind::PowerUp => "Power-ups",
+                    PickupKind::Ability => "Abilities",
+                };
+
+                format!("{} {}/{}", name, score.count(*kind), totals.count(*kind))
+            })
+            .collect();
+        lines.push(format!("Score {}", score.points()));
+
+        self.with_notes(lines)
+    }
+
+    /// Extra lines under the score, like warnings about how the result came about.
+    pub fn with_notes(mut self, notes: Vec<String>) -> Self {
+        let font = self.font;
+        self.lines.extend(notes.into_iter().map(|line| Self::line(font, line)));
+        self
+    }
+
+    /// Also lists the best runs on `seed`, once the leaderboard answers.
+    pub fn with_leaderboard(mut self, seed: u32) -> Self {
+        self.leaderboard_seed = Some(seed);
+        self
+    }
+
+    fn line(font: graphics::Font, line: String) -> graphics::Text {
+        let fragment = graphics::TextFragment::new(line)
+            .color(graphics::WHITE)
+            .font(font)
+            .scale(graphics::Scale::uniform(28.0));
+
+        graphics::Text::new(fragment)
+    }
+
+    fn set_leaderboard_lines(&mut self, lines: Vec<String>) {
+        let font = self.font;
+        self.leaderboard_lines = lines.into_iter().map(|line| Self::line(font, line)).collect();
+    }
+
+    fn update_leaderboard(&mut self, world: &World) {
+        let leaderboard = match &world.leaderboard {
+            Some(leaderboard) => leaderboard,
+            None => return,
+        };
+
+        if let Some(seed) = self.leaderboard_seed.take() {
+            leaderboard.fetch_top(seed, LEADERBOARD_ENTRIES);
+            self.set_leaderboard_lines(vec!{"Loading the leaderboard...".to_string()});
+        }
+
+        for event in leaderboard.poll() {
+            match event {
+                LeaderboardEvent::Scores(scores) => {
+                    let mut lines = vec!{"Leaderboard".to_string()};
+                    lines.extend(scores.entries.iter().enumerate().map(|(i, run)| {
+                        format!("{}. {:.1}s, {:.0} high", i + 1, run.time, run.height)
+                    }));
+                    self.set_leaderboard_lines(lines);
+                },
+                LeaderboardEvent::Unreachable(e) => {
+                    log::warn!("Leaderboard unreachable: {}", e);
+                    self.set_leaderboard_lines(vec!{"Leaderboard offline, the run is sent later".to_string()});
+                },
+                LeaderboardEvent::Submitted => (),
+            }
+        }
+    }
+}
+
+impl Scene<World> for GameOverScene {
+    fn update(
+        &mut self, 
+        ctx: &mut Context, 
+        world: &mut World
+    ) -> Option<Box<dyn Scene<World>>> {
+        self.update_leaderboard(world);
+
+        if self.is_done {
+            Some(Box::new(menu::MenuScene::new(ctx)))
+        } else {
+            None
+        }
+    }
+
+    fn draw(
+        &mut self, 
+        ctx: &mut Context, 
+        _world: &mut World
+    ) {
+        let (window_width, window_height) = graphics::drawable_size(ctx);
+
+        let title_text_width = self.title_text.width(ctx) as f32;
+        let title_text_height = self.title_text.height(ctx) as f32;
+
+        let lines: Vec<&graphics::Text> = self.lines
+            .iter()
+            .chain(self.leaderboard_lines.iter())
+            .chain(std::iter::once(&self.continue_text))
+            .collect();
+
+        let lines_height: f32 = lines
+            .iter()
+            .map(|line| line.height(ctx) as f32 + 10.0)
+            .sum();
+        let mut y = (window_height / 2.0) - (title_text_height + 20.0 + lines_height) / 2.0;
+
+        graphics::draw(
+            ctx,
+            &self.title_text,
+            graphics::DrawParam::new()
+                .dest(util::point_to_old(Point2::new(
+                    (window_width / 2.0) - (title_text_width / 2.0),
+                    y,
+                )))
+        )
+        .unwrap();
+        y += title_text_height + 20.0;
+
+        for line in lines {
+            let line_width = line.width(ctx) as f32;
+
+            graphics::draw(
+                ctx,
+                line,
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(
+                        (window_width / 2.0) - (line_width / 2.0),
+                        y,
+                    )))
+            )
+            .unwrap();
+            y += line.height(ctx) as f32 + 10.0;
+        }
+    }
+    
+    fn input(
+        &mut self, 
+        _world: &mut World, 
+        keycode: KeyCode, 
+        pressed: bool, 
+        _repeat: bool
+    ) {
+        if pressed && keycode == KeyCode::Space {
+            self.is_done = true;
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "Game Over Scene"
+    }
+}
\ No newline at end of file
@@ -1,19 +1,39 @@
-use crate::{Context, graphics, Scene, World, KeyCode, platform::Platform, player::Player, util};
+use crate::{Context, filesystem, graphics, timer, Scene, World, KeyCode, controls::{Controls, KeySet}, gfx::{DrawPawnType, GfxUtil, PawnDrawData}, platform::Platform, player::{Ability, Abilities, Player, PlayerInput}, movement::MovementProfile, lava::Lava, checkpoint::{Checkpoint, RespawnRules}, pickup::{Pickup, PickupHook, PickupKind, PickupParticles, PickupSounds}, power_up::{PowerUpKind, PowerUpTable}, save::{Replay, Run, SaveData}, leaderboard::{ReplayHash, RunResult}, generator::Rng, score::Score, snapshot::WorldSnapshot, physics::{ColliderHandle, ForceZone, ForceZoneHandle, ForceZoneKind, GravityZone, GravityZoneHandle, ObjectType, PhysicsEventKind, RemovalQueue, TIME_STEP}, util, game_over};
 use nalgebra::{Point2, Vector2};
 
-#[allow(dead_code)]
-pub struct LevelScene {
-    platforms: Vec<Platform>,
-    player: Player,
+use std::path::PathBuf;
 
-    is_done: bool,
+use ggez::event::{Axis, Button, GamepadId};
+
+use ncollide2d::bounding_volume::AABB;
+
+/// Platforms and zones of the level, shared by every scene that plays it.
+pub struct Layout {
+    pub platforms: Vec<Platform>,
+    pub checkpoints: Vec<Checkpoint>,
+    /// Only the ones not collected yet.
+    pub pickups: Vec<Pickup>,
+    /// Where the first player starts.
+    pub spawn: Point2<f32>,
+    /// Pixels per second.
+    pub lava_rise_speed: f32,
+    /// What every player starts with, ability pickups grant the rest.
+    pub abilities: Abilities,
+    gravity_zones: Vec<GravityZoneHandle>,
+    force_zones: Vec<ForceZoneHandle>,
+
+    removal_queue: RemovalQueue,
 }
 
-impl LevelScene {
-    pub fn new(
-        _ctx: &mut Context, 
-        world: &mut World
-    ) -> Self {
+impl Layout {
+    /// What the save file keeps this level's records under.
+    pub const NAME: &'static str = "Lava floor";
+    /// Top of the floor, heights are measured from it.
+    pub const FLOOR_Y: f32 = 299.;
+    /// What the leaderboard knows the hand-made layout by.
+    pub const SEED: u32 = 0;
+
+    pub fn new(world: &mut World) -> Self {
         let platforms = vec!{
             Platform::new(
                 Vector2::new(800., 1.), 
@@ -39,31 +59,300 @@ impl LevelScene {
                 Point2::new(100., 10.), 
                 world
             ),
+            Platform::new(
+                Vector2::new(50., 1.), 
+                Vector2::new(400., 150.), 
+                Point2::new(100., 10.), 
+                world
+            ),
+            // Walkable ramp up to the last column
+            Platform::sloped(
+                Vector2::new(40., 1.), 
+                Vector2::new(560., 280.), 
+                Point2::new(100., 10.), 
+                0.5,
+                world
+            ),
+            // Too steep to stand on, slides like a wall
+            Platform::sloped(
+                Vector2::new(40., 1.), 
+                Vector2::new(-60., 270.), 
+                Point2::new(100., 10.), 
+                1.1,
+                world
+            ),
+        };
+
+        let gravity = world.physics.get_gravity();
+        let gravity_zones = vec!{
+            // Low gravity between the first two columns
+            world.physics.add_gravity_zone(GravityZone::new(
+                AABB::new(Point2::new(120., 150.), Point2::new(280., 299.)),
+                gravity * 0.3,
+            )),
+            // Flipped gravity under the ceiling between the last two columns
+            world.physics.add_gravity_zone(GravityZone::new(
+                AABB::new(Point2::new(350., 151.), Point2::new(450., 299.)),
+                -gravity,
+            )),
+        };
+
+        let force_zones = vec!{
+            // Gusts blowing back towards the start past the last column
+            world.physics.add_force_zone(ForceZone::wind(
+                AABB::new(Point2::new(600., 150.), Point2::new(780., 299.)),
+                -120.,
+            ).with_gusts(3.)),
+            // Heat rising from a vent left of the start
+            world.physics.add_force_zone(ForceZone::updraft(
+                AABB::new(Point2::new(-200., 50.), Point2::new(-140., 299.)),
+                200.,
+            )),
         };
 
-        let player = Player::new(world);
+        // On the first column, the ledge past the flipped gravity and the last column
+        let checkpoints = vec!{
+            Checkpoint::new(Point2::new(100., 229.), world),
+            Checkpoint::new(Point2::new(400., 128.), world),
+            Checkpoint::new(Point2::new(500., 219.), world),
+        };
+
+        let pickups = vec!{
+            // A trail along the floor and over the first two columns
+            Pickup::new(PickupKind::Coin, Point2::new(-100., 285.), world),
+            Pickup::new(PickupKind::Coin, Point2::new(0., 285.), world),
+            Pickup::new(PickupKind::Coin, Point2::new(100., 235.), world),
+            Pickup::new(PickupKind::Coin, Point2::new(200., 285.), world),
+            Pickup::new(PickupKind::Coin, Point2::new(300., 245.), world),
+            // Up the updraft and on the ledge past the flipped gravity
+            Pickup::new(PickupKind::Gem, Point2::new(-170., 80.), world),
+            Pickup::new(PickupKind::Gem, Point2::new(430., 140.), world),
+            // Springs at the start, a heat shield at the vent and a feather in the low gravity
+            Pickup::power_up(PowerUpKind::Springs, Point2::new(-40., 285.), world),
+            Pickup::power_up(PowerUpKind::HeatShield, Point2::new(-170., 240.), world),
+            Pickup::power_up(PowerUpKind::Feather, Point2::new(200., 200.), world),
+            // Against the wind past the last column
+            Pickup::power_up(PowerUpKind::LavaFreeze, Point2::new(700., 285.), world),
+        };
 
         Self {
             platforms,
-            player,
-            is_done: false,
+            checkpoints,
+            pickups,
+            spawn: Point2::new(0., 0.),
+            lava_rise_speed: 10.,
+            abilities: Abilities {
+                double_jump: true,
+                air_dash: true,
+                grapple: true,
+            },
+            gravity_zones,
+            force_zones,
+            removal_queue: world.physics.removal_queue(),
         }
     }
 
-    fn draw_colliders(&mut self, ctx: &mut Context, world: &mut World) {
-        for (_, collider) in world.physics.colliders.iter() {
-            let shape = collider.shape().aabb(collider.position());
-            let rect = graphics::Rect::new(
-                shape.mins().x,
-                shape.mins().y,
-                shape.extents().x,
-                shape.extents().y,
-            );
+    /// A climb of ledges laid out from `seed`, with coins along the way and a gem
+    /// at the top. The same seed always gives the same level and lava speed.
+    /// Players start with the double jump and find the air dash and the grapple on
+    /// the way up.
+    pub fn generate(seed: u32, world: &mut World) -> Self {
+        let mut rng = Rng::new(seed);
+
+        let mut platforms = vec!{
+            Platform::new(
+                Vector2::new(800., 1.), 
+                Vector2::new(0., 300.), 
+                Point2::new(0., 0.), 
+                world
+            ),
+        };
+        let mut pickups = Vec::new();
+
+        // Each ledge is within a jump of the one below, starting over the spawn's head
+        let mut x = 0.;
+        let mut y = Self::FLOOR_Y - 30.;
+        for step in 0..GENERATED_LEDGES {
+            y -= rng.range(35., 55.);
+            x = (x + rng.range(-120., 120.)).clamp(-300., 300.);
+
+            platforms.push(Platform::new(
+                Vector2::new(rng.range(20., 50.), 1.), 
+                Vector2::new(x, y), 
+                Point2::new(100., 10.), 
+                world
+            ));
+
+            let above = Point2::new(x, y - 15.);
+            if step == GENERATED_LEDGES - 1 {
+                pickups.push(Pickup::new(PickupKind::Gem, above, world));
+            } else if let Some((_, ability)) = GENERATED_ABILITIES.iter().find(|(ledge, _)| *ledge == step) {
+                pickups.push(Pickup::ability(*ability, above, world));
+            } else if rng.chance(0.15) {
+                let kind = PowerUpKind::ALL[rng.below(PowerUpKind::ALL.len())];
+                pickups.push(Pickup::power_up(kind, above, world));
+            } else if rng.chance(0.5) {
+                pickups.push(Pickup::new(PickupKind::Coin, above, world));
+            }
+        }
 
-            let circle = graphics::Mesh::new_rectangle(
+        // Sometimes a band of gusts across the climb
+        let mut force_zones = Vec::new();
+        if rng.chance(0.5) {
+            let top = Self::FLOOR_Y - rng.range(100., (Self::FLOOR_Y - y).max(101.));
+            let speed = rng.range(60., 120.) * if rng.chance(0.5) { 1. } else { -1. };
+
+            force_zones.push(world.physics.add_force_zone(ForceZone::wind(
+                AABB::new(Point2::new(-400., top), Point2::new(400., top + 60.)),
+                speed,
+            ).with_gusts(3.)));
+        }
+
+        Self {
+            platforms,
+            checkpoints: Vec::new(),
+            pickups,
+            spawn: Point2::new(0., Self::FLOOR_Y - 20.),
+            lava_rise_speed: rng.range(8., 14.),
+            abilities: Abilities {
+                double_jump: true,
+                ..Default::default()
+            },
+            gravity_zones: Vec::new(),
+            force_zones,
+            removal_queue: world.physics.removal_queue(),
+        }
+    }
+
+    /// `count` players side by side at the spawn, with the abilities the level starts with.
+    pub fn spawn_players(&self, count: usize, world: &mut World) -> Vec<Player> {
+        (0..count)
+            .map(|i| {
+                let x = (i as f32 - (count - 1) as f32 / 2.) * SPAWN_SPACING;
+                let mut player = Player::spawn_at(self.spawn + Vector2::new(x, 0.), world);
+                player.abilities = self.abilities;
+
+                player
+            })
+            .collect()
+    }
+
+    /// Starts under the floor and rises at the level's speed.
+    pub fn spawn_lava(&self, world: &mut World) -> Lava {
+        Lava::new(LAVA_START_Y, LAVA_WIDTH, self.lava_rise_speed, world)
+    }
+
+    /// Everything but the lava and the players, which the scenes own.
+    pub fn draw(&self, ctx: &mut Context, world: &World) {
+        self.draw_gravity_zones(ctx, world);
+        self.draw_force_zones(ctx, world);
+        self.draw_checkpoints(ctx);
+        self.draw_pickups(ctx);
+        self.draw_colliders(ctx, world);
+        self.draw_ropes(ctx, world);
+    }
+
+    /// A pole with a pennant, green once someone has reached it.
+    fn draw_checkpoints(&self, ctx: &mut Context) {
+        for checkpoint in self.checkpoints.iter() {
+            let color = if checkpoint.is_reached {
+                graphics::Color::from((60, 200, 90, 255))
+            } else {
+                graphics::Color::from((140, 140, 140, 255))
+            };
+
+            let bottom = checkpoint.position + Vector2::new(0., checkpoint.height() / 2.);
+            let top = checkpoint.position - Vector2::new(0., checkpoint.height() / 2.);
+
+            let mut builder = graphics::MeshBuilder::new();
+            builder.line(&[util::point_to_old(bottom), util::point_to_old(top)], 2., graphics::WHITE)
+                .unwrap();
+            builder.triangles(
+                &[
+                    util::point_to_old(top),
+                    util::point_to_old(top + Vector2::new(12., 5.)),
+                    util::point_to_old(top + Vector2::new(0., 10.)),
+                ],
+                color,
+            )
+            .unwrap();
+
+            let mesh = builder.build(ctx).unwrap();
+
+            graphics::draw(
+                ctx, 
+                &mesh, 
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(100., 100.))),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Coins are discs, gems diamonds, power-ups rings and abilities arrows.
+    fn draw_pickups(&self, ctx: &mut Context) {
+        if self.pickups.is_empty() {
+            return;
+        }
+
+        let mut builder = graphics::MeshBuilder::new();
+        for pickup in self.pickups.iter() {
+            let center = pickup.position;
+            let radius = pickup.radius();
+
+            match pickup.kind {
+                PickupKind::Coin => {
+                    builder.circle(graphics::DrawMode::fill(), util::point_to_old(center), radius, 0.5, pickup.color());
+                },
+                PickupKind::Gem => {
+                    builder.polygon(
+                        graphics::DrawMode::fill(),
+                        &[
+                            util::point_to_old(center - Vector2::new(0., radius)),
+                            util::point_to_old(center + Vector2::new(radius, 0.)),
+                            util::point_to_old(center + Vector2::new(0., radius)),
+                            util::point_to_old(center - Vector2::new(radius, 0.)),
+                        ],
+                        pickup.color(),
+                    )
+                    .unwrap();
+                },
+                PickupKind::PowerUp => {
+                    builder.circle(graphics::DrawMode::stroke(2.), util::point_to_old(center), radius, 0.5, pickup.color());
+                },
+                PickupKind::Ability => {
+                    builder.triangles(
+                        &[
+                            util::point_to_old(center - Vector2::new(0., radius)),
+                            util::point_to_old(center + Vector2::new(radius, radius)),
+                            util::point_to_old(center + Vector2::new(-radius, radius)),
+                        ],
+                        pickup.color(),
+                    )
+                    .unwrap();
+                },
+            };
+        }
+
+        let mesh = builder.build(ctx).unwrap();
+
+        graphics::draw(
+            ctx, 
+            &mesh, 
+            graphics::DrawParam::new()
+                .dest(util::point_to_old(Point2::new(100., 100.))),
+        )
+        .unwrap();
+    }
+
+    fn draw_colliders(&self, ctx: &mut Context, world: &World) {
+        for outline in world.physics.collider_outlines() {
+            let points: Vec<_> = outline.into_iter().map(util::point_to_old).collect();
+
+            let circle = graphics::Mesh::new_polygon(
                 ctx,
                 graphics::DrawMode::Stroke(graphics::StrokeOptions::DEFAULT),
-                rect,
+                &points,
                 graphics::WHITE,
             )
             .unwrap();
@@ -77,6 +366,771 @@ impl LevelScene {
             .unwrap();
         }
     }
+
+    fn draw_ropes(&self, ctx: &mut Context, world: &World) {
+        for (hook, attached) in world.physics.ropes() {
+            let mesh = graphics::Mesh::new_line(
+                ctx,
+                &[util::point_to_old(hook), util::point_to_old(attached)],
+                2.,
+                graphics::Color::from((139, 90, 43, 255)),
+            )
+            .unwrap();
+
+            graphics::draw(
+                ctx, 
+                &mesh, 
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(100., 100.))),
+            )
+            .unwrap();
+        }
+    }
+
+    fn draw_force_zones(&self, ctx: &mut Context, world: &World) {
+        for (zone, blowing) in world.physics.force_zones() {
+            let rect = graphics::Rect::new(
+                zone.area.mins().x,
+                zone.area.mins().y,
+                zone.area.extents().x,
+                zone.area.extents().y,
+            );
+
+            let (r, g, b) = match zone.kind {
+                ForceZoneKind::Wind => (0.8, 0.9, 1.0),
+                ForceZoneKind::Updraft => (1.0, 0.5, 0.1),
+            };
+            // Gusts fade out between blows
+            let alpha = if blowing { 0.25 } else { 0.08 };
+
+            let mut builder = graphics::MeshBuilder::new();
+            builder.rectangle(graphics::DrawMode::fill(), rect, graphics::Color::new(r, g, b, alpha));
+
+            // Arrow from the center in the direction of the push
+            if let Some(direction) = zone.acceleration.try_normalize(f32::EPSILON) {
+                let center = zone.area.center();
+                let tip = center + direction * 20.;
+                let side = Vector2::new(-direction.y, direction.x) * 6.;
+                let back = tip - direction * 8.;
+
+                builder.line(
+                    &[util::point_to_old(center - direction * 20.), util::point_to_old(tip)],
+                    2.,
+                    graphics::Color::new(r, g, b, alpha * 3.),
+                )
+                .unwrap();
+                builder.triangles(
+                    &[util::point_to_old(tip), util::point_to_old(back + side), util::point_to_old(back - side)],
+                    graphics::Color::new(r, g, b, alpha * 3.),
+                )
+                .unwrap();
+            }
+
+            let mesh = builder.build(ctx).unwrap();
+
+            graphics::draw(
+                ctx, 
+                &mesh, 
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(100., 100.))),
+            )
+            .unwrap();
+        }
+    }
+
+    fn draw_gravity_zones(&self, ctx: &mut Context, world: &World) {
+        let gravity = world.physics.get_gravity();
+
+        for zone in world.physics.gravity_zones() {
+            let rect = graphics::Rect::new(
+                zone.area.mins().x,
+                zone.area.mins().y,
+                zone.area.extents().x,
+                zone.area.extents().y,
+            );
+
+            let color = if zone.gravity.y * gravity.y < 0. {
+                graphics::Color::new(0.6, 0.2, 0.8, 0.25)
+            } else {
+                graphics::Color::new(0.2, 0.6, 1.0, 0.25)
+            };
+
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                rect,
+                color,
+            )
+            .unwrap();
+
+            graphics::draw(
+                ctx, 
+                &mesh, 
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(100., 100.))),
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Entities despawn themselves, the zones are owned by the layout.
+impl Drop for Layout {
+    fn drop(&mut self) {
+        for handle in self.gravity_zones.drain(..) {
+            self.removal_queue.remove_gravity_zone(handle);
+        }
+        for handle in self.force_zones.drain(..) {
+            self.removal_queue.remove_force_zone(handle);
+        }
+    }
+}
+
+pub fn draw_lava(ctx: &mut Context, lava: &Lava) {
+    let rect = graphics::Rect::new(
+        -lava.width / 2.,
+        lava.height,
+        lava.width,
+        graphics::drawable_size(ctx).1,
+    );
+
+    let mesh = graphics::Mesh::new_rectangle(
+        ctx,
+        graphics::DrawMode::fill(),
+        rect,
+        // Cools down to a dull red while frozen
+        if lava.is_frozen() {
+            graphics::Color::from((120, 40, 60, 255))
+        } else {
+            graphics::Color::from((207, 16, 32, 255))
+        },
+    )
+    .unwrap();
+
+    graphics::draw(
+        ctx, 
+        &mesh, 
+        graphics::DrawParam::new()
+            .dest(util::point_to_old(Point2::new(100., 100.))),
+    )
+    .unwrap();
+}
+
+/// Colliders that started overlapping a sensor of `kind` in the last step, each
+/// paired with that sensor.
+fn sensor_hits(world: &World, kind: ObjectType) -> Vec<(ColliderHandle, ColliderHandle)> {
+    world.physics.events()
+        .iter()
+        .filter(|event| event.kind == PhysicsEventKind::ProximityStarted)
+        .filter_map(|event| {
+            if event.entities.0.kind == kind {
+                Some((event.colliders.1, event.colliders.0))
+            } else if event.entities.1.kind == kind {
+                Some((event.colliders.0, event.colliders.1))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Sprite tint of the player at `index`, in the order they join.
+pub fn tint(index: usize) -> graphics::Color {
+    TINTS[index % TINTS.len()].into()
+}
+
+const TINTS: [(u8, u8, u8); 4] = [
+    (255, 255, 255),
+    (90, 170, 255),
+    (120, 220, 120),
+    (250, 200, 60),
+];
+/// Space between players when they spawn.
+const SPAWN_SPACING: f32 = 40.;
+/// Where the lava's surface starts, below the floor.
+const LAVA_START_Y: f32 = 320.;
+const LAVA_WIDTH: f32 = 1600.;
+/// Ledges in a generated level.
+const GENERATED_LEDGES: usize = 14;
+/// Which ledge of a generated level holds each ability players don't start with.
+const GENERATED_ABILITIES: [(usize, Ability); 2] = [
+    (4, Ability::AirDash),
+    (9, Ability::Grapple),
+];
+/// How far below a player a heat shield pushes the lava.
+const SHIELD_CLEARANCE: f32 = 20.;
+/// Side of a power-up icon in the HUD.
+const HUD_ICON_SIZE: f32 = 16.;
+/// Seconds gamepad players have to press a button, racers still without one are left out.
+const GAMEPAD_JOIN_TIME: f32 = 5.;
+
+/// A daily challenge in progress.
+struct DailyRun {
+    /// False once the day's attempt is spent, later runs are only practice.
+    counts: bool,
+}
+
+/// A player in the level, with whoever controls it.
+struct Racer {
+    /// Counted from 1, as shown to the players.
+    number: usize,
+    player: Player,
+    controls: Controls,
+    tint: graphics::Color,
+}
+
+/// What plays out in a level, with nothing that needs a window, so a daily
+/// challenge's replay can be played back from the command line.
+pub struct Race {
+    layout: Layout,
+    /// Only the ones still standing, players that touch the lava are taken out.
+    racers: Vec<Racer>,
+    lava: Lava,
+    /// Last one standing wins, instead of playing until the only player falls.
+    is_multiplayer: bool,
+    /// Players with a checkpoint respawn at it instead of being taken out.
+    respawn: Option<RespawnRules>,
+
+    /// Seconds since the level started.
+    elapsed: f32,
+    /// Highest any player got above the floor.
+    max_height: f32,
+    /// `PlayerInput::to_bits` of every player still standing, frame after frame.
+    inputs: Vec<u8>,
+
+    is_done: bool,
+    /// Number of the last player standing, in multiplayer.
+    winner: Option<usize>,
+}
+
+impl Race {
+    /// One player per entry in `controls`, the score and the physics clock start over.
+    pub fn new(world: &mut World, layout: Layout, controls: Vec<Controls>) -> Self {
+        world.score = Score::default();
+        // The world outlives levels, gusts have to blow as they do on a fresh one for
+        // a replay to play back the same
+        world.physics.set_clock(0, 0.);
+
+        let player_count = controls.len();
+        let racers = layout.spawn_players(player_count, world)
+            .into_iter()
+            .zip(controls)
+            .enumerate()
+            .map(|(i, (player, controls))| Racer {
+                number: i + 1,
+                player,
+                controls,
+                tint: tint(i),
+            })
+            .collect();
+
+        let lava = layout.spawn_lava(world);
+
+        Self {
+            layout,
+            racers,
+            lava,
+            is_multiplayer: player_count > 1,
+            respawn: None,
+            elapsed: 0.,
+            max_height: 0.,
+            inputs: Vec::new(),
+            is_done: false,
+            winner: None,
+        }
+    }
+
+    /// The level generated from `seed` with a single player.
+    pub fn daily(world: &mut World, seed: u32) -> Self {
+        let layout = Layout::generate(seed, world);
+
+        Self::new(world, layout, vec!{Controls::Keys(KeySet::single())})
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.is_done
+    }
+
+    pub fn run(&self, world: &World) -> Run {
+        Run {
+            time: self.elapsed,
+            height: self.max_height,
+            coins: world.score.coins,
+        }
+    }
+
+    /// Plays one frame with the inputs the players hold, returns the pickups
+    /// collected in it and where they were.
+    pub fn step(&mut self, world: &mut World, dt: f32) -> Vec<(PickupKind, Point2<f32>)> {
+        world.physics.step();
+
+        self.reach_checkpoints(world);
+        let collected = self.collect_pickups(world);
+        self.remove_burnt(world);
+        if self.is_multiplayer && self.racers.len() <= 1 {
+            self.winner = self.racers.first().map(|racer| racer.number);
+            self.is_done = true;
+        } else if self.racers.is_empty() {
+            self.is_done = true;
+        }
+
+        self.elapsed += dt;
+        if self.elapsed >= GAMEPAD_JOIN_TIME {
+            self.drop_unclaimed(world);
+        }
+        self.inputs.extend(self.racers.iter().map(|racer| racer.player.input.to_bits()));
+        self.lava.update(dt, world);
+        for racer in self.racers.iter_mut() {
+            racer.player.update(dt, world);
+            self.max_height = self.max_height.max(Layout::FLOOR_Y - racer.player.position.y);
+        }
+
+        collected
+    }
+
+    /// `None` once a player's body is gone.
+    pub fn snapshot(&self, world: &World) -> Option<WorldSnapshot> {
+        WorldSnapshot::capture(
+            world,
+            &self.layout,
+            self.racers.iter().map(|racer| &racer.player),
+            &self.lava,
+        )
+    }
+
+    /// Players are matched up in order, ones out since the snapshot stay out.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot, world: &mut World) {
+        snapshot.restore(
+            world,
+            &mut self.layout,
+            self.racers.iter_mut().map(|racer| &mut racer.player),
+            &mut self.lava,
+        );
+    }
+
+    /// Of every input so far and the seed, what the leaderboard checks a run by.
+    pub fn replay_hash(&self, seed: u32) -> String {
+        let mut hash = ReplayHash::default();
+        hash.write(&self.inputs);
+        hash.write(&seed.to_le_bytes());
+
+        hash.hex()
+    }
+
+    /// Remembers where each player touched a checkpoint.
+    fn reach_checkpoints(&mut self, world: &World) {
+        for (collider, flag) in sensor_hits(world, ObjectType::Checkpoint) {
+            let racer = self.racers.iter_mut().find(|racer| racer.player.collider_handle() == collider);
+            let checkpoint = self.layout.checkpoints.iter_mut().find(|checkpoint| checkpoint.collider_handle() == flag);
+
+            if let (Some(racer), Some(checkpoint)) = (racer, checkpoint) {
+                racer.player.checkpoint = Some(racer.player.position);
+                checkpoint.is_reached = true;
+            }
+        }
+    }
+
+    /// Scores every pickup a player touched and starts its power-up or grants its ability.
+    fn collect_pickups(&mut self, world: &mut World) -> Vec<(PickupKind, Point2<f32>)> {
+        let mut collected = Vec::new();
+        for (collider, sensor) in sensor_hits(world, ObjectType::Pickup) {
+            let racer = match self.racers.iter_mut().position(|racer| racer.player.collider_handle() == collider) {
+                Some(racer) => racer,
+                None => continue,
+            };
+
+            // Already gone when two players touch it in the same step
+            let index = match self.layout.pickups.iter().position(|pickup| pickup.collider_handle() == sensor) {
+                Some(index) => index,
+                None => continue,
+            };
+            let pickup = self.layout.pickups.remove(index);
+
+            world.score.add(pickup.kind);
+            if let Some(kind) = pickup.power_up {
+                let effect = world.power_ups.get(kind);
+                if effect.freezes_lava {
+                    self.lava.freeze(effect);
+                }
+                if effect.affects_player() {
+                    self.racers[racer].player.power_ups.add(kind, effect);
+                }
+            }
+            if let Some(ability) = pickup.ability {
+                self.racers[racer].player.grant_ability(ability);
+            }
+
+            collected.push((pickup.kind, pickup.position));
+        }
+
+        collected
+    }
+
+    /// Racers still waiting for a gamepad once the time to join is up.
+    fn drop_unclaimed(&mut self, world: &mut World) {
+        let notices = &mut world.notices;
+        self.racers.retain(|racer| {
+            let is_unclaimed = racer.controls.is_waiting_for_gamepad();
+            if is_unclaimed {
+                notices.show(format!("No gamepad joined, player {} sits out", racer.number));
+            }
+
+            !is_unclaimed
+        });
+    }
+
+    /// Takes out every player that touched the lava in the last step, unless a heat
+    /// shield saves them or they can respawn at a checkpoint.
+    fn remove_burnt(&mut self, world: &mut World) {
+        let burnt: Vec<ColliderHandle> = sensor_hits(world, ObjectType::Lava)
+            .into_iter()
+            .map(|(collider, _)| collider)
+            .collect();
+
+        let mut survived = Vec::new();
+        let mut shielded = Vec::new();
+        for racer in self.racers.iter_mut().filter(|racer| burnt.contains(&racer.player.collider_handle())) {
+            if racer.player.power_ups.absorb_lava() {
+                // Sinks the lava under the player, so only the next touch counts
+                self.lava.push_back(racer.player.position.y + SHIELD_CLEARANCE, world);
+                survived.push(racer.player.collider_handle());
+                shielded.push(racer.player.collider_handle());
+            } else if let Some((rules, checkpoint)) = self.respawn.zip(racer.player.checkpoint) {
+                racer.player.respawn(checkpoint, world);
+                self.lava.push_back(checkpoint.y + rules.lava_clearance, world);
+                survived.push(racer.player.collider_handle());
+            }
+        }
+
+        let deaths = burnt
+            .iter()
+            .filter(|handle| self.racers.iter().any(|racer| racer.player.collider_handle() == **handle))
+            .filter(|handle| !shielded.contains(handle))
+            .count();
+        world.save.total_deaths += deaths as u32;
+
+        let is_multiplayer = self.is_multiplayer;
+        let notices = &mut world.notices;
+        self.racers.retain(|racer| {
+            let handle = racer.player.collider_handle();
+            let is_out = burnt.contains(&handle) && !survived.contains(&handle);
+            if is_out && is_multiplayer {
+                notices.show(format!("Player {} is out", racer.number));
+            }
+
+            !is_out
+        });
+    }
+}
+
+/// Plays a daily challenge's replay back the way it was played, on fixed time steps
+/// with the default tuning ranked runs have, and checks it ends as it claims.
+pub fn verify_replay(replay: &Replay) -> Result<(), String> {
+    let inputs = replay.inputs().ok_or("The inputs aren't valid hex")?;
+
+    let mut world = World::new(replay.physics);
+    let mut race = Race::daily(&mut world, replay.seed);
+
+    // The frame the run ends on isn't recorded, nothing is held during it
+    let mut frames = inputs.into_iter().map(Some).chain(std::iter::once(None));
+    while !race.is_done() {
+        let bits = frames.next().ok_or("The inputs run out before the run ends")?;
+        if let Some(racer) = race.racers.first_mut() {
+            racer.player.input = PlayerInput::from_bits(bits.unwrap_or(0));
+        }
+        race.step(&mut world, TIME_STEP);
+    }
+    if frames.next().is_some() {
+        return Err("The run ends before the inputs do".to_string());
+    }
+
+    if race.replay_hash(replay.seed) != replay.replay_hash {
+        return Err(format!("The inputs hash to {}, not {}", race.replay_hash(replay.seed), replay.replay_hash));
+    }
+
+    let run = race.run(&world);
+    if run.time != replay.time || run.height != replay.height {
+        return Err(format!(
+            "Played back to {:.2}s and {:.1} high, the replay claims {:.2}s and {:.1} high",
+            run.time, run.height, replay.time, replay.height,
+        ));
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub struct LevelScene {
+    race: Race,
+    gfx: GfxUtil,
+    /// Every pickup the level started with.
+    totals: Score,
+    pickup_hooks: Vec<Box<dyn PickupHook>>,
+
+    /// Of the layout, for the leaderboard.
+    seed: u32,
+    /// Runs on fixed time steps, so the replay plays back the same.
+    daily: Option<DailyRun>,
+    /// Set once the movement or power-ups were tuned away from the defaults at any
+    /// point of the run, it can't be compared with the others then.
+    custom_tuning: bool,
+
+    /// Last quick-save, also written to `quicksave_path` for the next session.
+    quicksave: Option<WorldSnapshot>,
+    quicksave_path: PathBuf,
+}
+
+impl LevelScene {
+    pub fn new(
+        ctx: &mut Context, 
+        world: &mut World
+    ) -> Self {
+        Self::with_controls(ctx, world, vec!{Controls::Keys(KeySet::single())})
+    }
+
+    /// A single player run where touching the lava sends the player back to their
+    /// last checkpoint, if they have reached one.
+    pub fn practice(
+        ctx: &mut Context, 
+        world: &mut World,
+        rules: RespawnRules,
+    ) -> Self {
+        let mut scene = Self::new(ctx, world);
+        scene.race.respawn = Some(rules);
+
+        scene
+    }
+
+    /// The first two players share the keyboard, anyone after them plays on a gamepad
+    /// and sits the race out if no gamepad joins for them in time.
+    pub fn local_multiplayer(
+        ctx: &mut Context, 
+        world: &mut World,
+        player_count: usize,
+    ) -> Self {
+        let controls = (0..player_count)
+            .map(|i| match i {
+                0 => Controls::Keys(KeySet::wasd()),
+                1 => Controls::Keys(KeySet::arrows()),
+                _ => Controls::Gamepad(None),
+            })
+            .collect();
+
+        if player_count > 2 {
+            world.notices.show(format!(
+                "Players 3 to {} join by pressing a button on a gamepad",
+                player_count,
+            ));
+        }
+
+        Self::with_controls(ctx, world, controls)
+    }
+
+    /// The level generated from `seed`, a new one every day. Only the first attempt
+    /// on a seed goes on record, and it is kept with a replay to check it by.
+    pub fn daily(
+        ctx: &mut Context, 
+        world: &mut World,
+        seed: u32,
+    ) -> Self {
+        let counts = world.save.start_daily(seed);
+        if counts {
+            // Spent right away, so quitting halfway doesn't earn another try
+            if let Err(e) = world.save.save(&SaveData::path(ctx)) {
+                log::error!("Failed to save records: {}", e);
+                world.notices.show("Failed to save your records");
+            }
+        }
+
+        let race = Race::daily(world, seed);
+        let mut scene = Self::with_race(ctx, race);
+        scene.seed = seed;
+        scene.daily = Some(DailyRun {
+            counts,
+        });
+
+        scene
+    }
+
+    /// One player per entry in `controls`.
+    pub fn with_controls(
+        ctx: &mut Context, 
+        world: &mut World,
+        controls: Vec<Controls>,
+    ) -> Self {
+        let layout = Layout::new(world);
+        let race = Race::new(world, layout, controls);
+
+        Self::with_race(ctx, race)
+    }
+
+    fn with_race(
+        ctx: &mut Context, 
+        race: Race,
+    ) -> Self {
+        let totals = Score::of(race.layout.pickups.iter().map(|pickup| pickup.kind));
+        let pickup_hooks: Vec<Box<dyn PickupHook>> = vec!{
+            Box::new(PickupParticles::default()),
+            Box::new(PickupSounds::new(ctx)),
+        };
+
+        Self {
+            race,
+            gfx: GfxUtil::new(ctx),
+            totals,
+            pickup_hooks,
+            seed: Layout::SEED,
+            daily: None,
+            custom_tuning: false,
+            quicksave: None,
+            quicksave_path: filesystem::user_data_dir(ctx).join("quicksave.toml"),
+        }
+    }
+
+    fn quicksave(&mut self, world: &mut World) {
+        let snapshot = match self.race.snapshot(world) {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+
+        match snapshot.save(&self.quicksave_path) {
+            Ok(()) => {
+                log::info!("Saved snapshot to {}", self.quicksave_path.display());
+                world.notices.show("Quick-saved");
+            },
+            Err(e) => {
+                log::error!("Failed to save snapshot: {}", e);
+                world.notices.show("Failed to quick-save");
+            },
+        }
+        self.quicksave = Some(snapshot);
+    }
+
+    /// Falls back to the file when nothing was saved this session.
+    fn quickload(&mut self, world: &mut World) {
+        let snapshot = match self.quicksave.clone() {
+            Some(snapshot) => snapshot,
+            None => match WorldSnapshot::load(&self.quicksave_path) {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    log::warn!("Failed to load snapshot: {}", e);
+                    world.notices.show("Nothing to quick-load");
+                    return;
+                },
+            },
+        };
+
+        self.race.restore(&snapshot, world);
+        self.quicksave = Some(snapshot);
+    }
+
+    /// Only single player runs without respawns compete, and on a daily challenge
+    /// only the first attempt.
+    fn is_ranked(&self) -> bool {
+        let counts = self.daily.as_ref().map(|daily| daily.counts).unwrap_or(true);
+
+        !self.race.is_multiplayer && self.race.respawn.is_none() && !self.custom_tuning && counts
+    }
+
+    /// Adds a ranked run to the records and the leaderboard, the deaths count either way.
+    fn save_run(&self, ctx: &Context, world: &mut World) {
+        let run = self.race.run(world);
+        let replay_hash = self.race.replay_hash(self.seed);
+
+        match &self.daily {
+            Some(_) if self.is_ranked() => {
+                world.save.finish_daily(self.seed, run, replay_hash.clone());
+
+                let path = Replay::path(ctx, self.seed);
+                let replay = Replay::new(self.seed, replay_hash.clone(), run, world.physics_kind, &self.race.inputs);
+                if let Err(e) = replay.save(&path) {
+                    log::error!("Failed to save replay: {}", e);
+                    world.notices.show("Failed to save the replay");
+                }
+            },
+            Some(_) => (),
+            None if self.is_ranked() => world.save.record_run(Layout::NAME, run),
+            None => (),
+        };
+
+        if let Err(e) = world.save.save(&SaveData::path(ctx)) {
+            log::error!("Failed to save records: {}", e);
+            world.notices.show("Failed to save your records");
+        }
+
+        if let (true, Some(leaderboard)) = (self.is_ranked(), &world.leaderboard) {
+            leaderboard.submit(RunResult {
+                seed: self.seed,
+                time: run.time,
+                height: run.height,
+                replay_hash,
+            });
+        }
+    }
+}
+
+/// A square in the power-up's color that empties from the top as it runs out.
+fn add_power_up_icon(builder: &mut graphics::MeshBuilder, origin: Point2<f32>, kind: PowerUpKind, fraction: f32) {
+    let mut faded = kind.color();
+    faded.a = 0.3;
+    let full = HUD_ICON_SIZE * fraction.clamp(0., 1.);
+
+    builder.rectangle(
+        graphics::DrawMode::fill(),
+        graphics::Rect::new(origin.x, origin.y, HUD_ICON_SIZE, HUD_ICON_SIZE),
+        faded,
+    );
+    builder.rectangle(
+        graphics::DrawMode::fill(),
+        graphics::Rect::new(origin.x, origin.y + HUD_ICON_SIZE - full, HUD_ICON_SIZE, full),
+        kind.color(),
+    );
+}
+
+impl LevelScene {
+    /// One row of icons per player under a swatch of their tint, with the lava
+    /// freeze in the top right corner.
+    fn draw_hud(&self, ctx: &mut Context, power_ups: &PowerUpTable) {
+        let mut builder = graphics::MeshBuilder::new();
+        let mut is_empty = true;
+
+        for (row, racer) in self.race.racers.iter().enumerate() {
+            if racer.player.power_ups.timers.is_empty() {
+                continue;
+            }
+            is_empty = false;
+
+            let y = 10. + row as f32 * (HUD_ICON_SIZE + 6.);
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(10., y + HUD_ICON_SIZE / 4., HUD_ICON_SIZE / 2., HUD_ICON_SIZE / 2.),
+                racer.tint,
+            );
+
+            for (column, timer) in racer.player.power_ups.timers.iter().enumerate() {
+                let x = 10. + HUD_ICON_SIZE + column as f32 * (HUD_ICON_SIZE + 4.);
+                let fraction = timer.remaining / power_ups.get(timer.kind).duration;
+                add_power_up_icon(&mut builder, Point2::new(x, y), timer.kind, fraction);
+            }
+        }
+
+        if self.race.lava.is_frozen() {
+            is_empty = false;
+
+            let x = graphics::drawable_size(ctx).0 - 10. - HUD_ICON_SIZE;
+            let fraction = self.race.lava.frozen_for / power_ups.lava_freeze.duration;
+            add_power_up_icon(&mut builder, Point2::new(x, 10.), PowerUpKind::LavaFreeze, fraction);
+        }
+
+        // Building an empty mesh fails
+        if is_empty {
+            return;
+        }
+
+        let mesh = builder.build(ctx).unwrap();
+
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    }
 }
 
 impl Scene<World> for LevelScene {
@@ -85,14 +1139,45 @@ impl Scene<World> for LevelScene {
         ctx: &mut Context, 
         world: &mut World
     ) -> Option<Box<dyn Scene<World>>> {
-        if self.is_done {
-            // TODO: Switch to game over scene
+        if self.race.is_done {
+            self.save_run(ctx, world);
+
+            let title = match (self.race.is_multiplayer, self.race.winner) {
+                (false, _) => match &self.daily {
+                    Some(daily) if daily.counts => format!("Daily challenge {}", self.seed),
+                    Some(_) => format!("Daily challenge {}, practice", self.seed),
+                    None => "Game over".to_string(),
+                },
+                (true, Some(number)) => format!("Player {} wins", number),
+                (true, None) => "Nobody made it".to_string(),
+            };
+
+            let mut game_over = game_over::GameOverScene::new(ctx, &title)
+                .with_score(world.score, self.totals);
+            if self.is_ranked() {
+                game_over = game_over.with_leaderboard(self.seed);
+            } else if self.custom_tuning {
+                game_over = game_over.with_notes(vec!{"Custom tuning, the run isn't ranked".to_string()});
+            }
+
+            return Some(Box::new(game_over));
         }
 
-        world.physics.step();
-        world.physics.ticks += 1;
+        let dt = match self.daily {
+            Some(_) => TIME_STEP,
+            None => timer::delta(ctx).as_secs_f32(),
+        };
+        self.custom_tuning |= world.movement != MovementProfile::default()
+            || world.power_ups != PowerUpTable::default();
 
-        self.player.update(ctx, world);
+        for (kind, position) in self.race.step(world, dt) {
+            for hook in self.pickup_hooks.iter_mut() {
+                hook.collected(ctx, kind, position);
+            }
+        }
+        for hook in self.pickup_hooks.iter_mut() {
+            hook.update(dt);
+        }
 
         None
     }
@@ -102,25 +1187,191 @@ impl Scene<World> for LevelScene {
         ctx: &mut Context, 
         world: &mut World
     ) {
-        self.draw_colliders(ctx, world);
+        self.race.layout.draw(ctx, world);
+
+        let pawns = self.race.racers
+            .iter()
+            .map(|racer| PawnDrawData {
+                position: racer.player.position,
+                rotation: 0.,
+                pawn_type: DrawPawnType::Player,
+                tint: racer.tint,
+            });
+        self.gfx.draw_pawns(ctx, pawns);
+
+        for hook in self.pickup_hooks.iter_mut() {
+            hook.draw(ctx);
+        }
+
+        draw_lava(ctx, &self.race.lava);
+
+        self.draw_hud(ctx, &world.power_ups);
     }
     
     fn input(
         &mut self, 
-        _world: &mut World, 
+        world: &mut World, 
         keycode: KeyCode, 
         pressed: bool, 
         repeat: bool
     ) {
+        // Loading would undo the daily challenge's one attempt
+        let can_quickload = self.daily.is_none();
+
         match keycode {
-            KeyCode::A | KeyCode::Left => self.player.input.left = pressed,
-            KeyCode::D | KeyCode::Right => self.player.input.right = pressed,
-            KeyCode::Space => self.player.input.jump = pressed && !repeat,
-            _ => (),
+            KeyCode::F5 if pressed && !repeat && can_quickload => self.quicksave(world),
+            KeyCode::F9 if pressed && !repeat && can_quickload => self.quickload(world),
+            _ => {
+                for racer in self.race.racers.iter_mut() {
+                    if racer.controls.key(&mut racer.player.input, keycode, pressed, repeat) {
+                        break;
+                    }
+                }
+            },
         };
     }
+
+    /// A gamepad no one uses yet joins the first player still waiting for one.
+    fn gamepad_button(
+        &mut self,
+        world: &mut World,
+        id: GamepadId,
+        button: Button,
+        pressed: bool
+    ) {
+        if !self.race.racers.iter().any(|racer| racer.controls.uses_gamepad(id)) {
+            for racer in self.race.racers.iter_mut() {
+                if racer.controls.claim_gamepad(id) {
+                    world.notices.show(format!("Gamepad joined as player {}", racer.number));
+                    break;
+                }
+            }
+        }
+
+        for racer in self.race.racers.iter_mut() {
+            if racer.controls.button(&mut racer.player.input, id, button, pressed) {
+                break;
+            }
+        }
+    }
+
+    fn gamepad_axis(
+        &mut self,
+        _world: &mut World,
+        id: GamepadId,
+        axis: Axis,
+        value: f32
+    ) {
+        for racer in self.race.racers.iter_mut() {
+            if racer.controls.axis(&mut racer.player.input, id, axis, value) {
+                break;
+            }
+        }
+    }
+
+    /// A daily keeps the profile it started with, its replay has to check out.
+    fn allows_hot_reload(&self) -> bool {
+        self.daily.is_none()
+    }
     
     fn name(&self) -> &str {
         "Level Scene"
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::PhysicsKind;
+
+    const SEED: u32 = 20_261_019;
+    /// The first seed after `SEED` with a band of gusts.
+    const GUSTY_SEED: u32 = 20_261_020;
+    /// Ten minutes, the lava is well past any ledge by then.
+    const MAX_FRAMES: u32 = 36_000;
+
+    /// Runs back and forth and jumps now and then until the lava gets the player.
+    fn play_daily(world: &mut World, seed: u32) -> Replay {
+        let mut race = Race::daily(world, seed);
+
+        let mut frame = 0;
+        while !race.is_done() {
+            assert!(frame < MAX_FRAMES);
+            if let Some(racer) = race.racers.first_mut() {
+                racer.player.input = PlayerInput {
+                    left: frame / 90 % 2 == 1,
+                    right: frame / 90 % 2 == 0,
+                    jump: frame % 40 < 10,
+                    ..Default::default()
+                };
+            }
+            race.step(world, TIME_STEP);
+            frame += 1;
+        }
+
+        Replay::new(seed, race.replay_hash(seed), race.run(world), world.physics_kind, &race.inputs)
+    }
+
+    #[test]
+    fn generated_levels_hand_out_the_abilities_players_start_without() {
+        let mut world = World::new(PhysicsKind::Aabb);
+        let race = Race::daily(&mut world, SEED);
+
+        let abilities = race.racers[0].player.abilities;
+        assert!(abilities.double_jump && !abilities.air_dash && !abilities.grapple);
+
+        let granted: Vec<Ability> = race.layout.pickups.iter().filter_map(|pickup| pickup.ability).collect();
+        assert_eq!(granted, vec!{Ability::AirDash, Ability::Grapple});
+    }
+
+    #[test]
+    fn replays_play_back_to_the_run_they_recorded() {
+        for physics in [PhysicsKind::Aabb, PhysicsKind::NPhysics].iter() {
+            let mut world = World::new(*physics);
+            assert_eq!(verify_replay(&play_daily(&mut world, SEED)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn replays_that_claim_too_much_are_caught() {
+        let mut replay = play_daily(&mut World::new(PhysicsKind::Aabb), SEED);
+        replay.height += 10.;
+        assert!(verify_replay(&replay).is_err());
+
+        let mut replay = play_daily(&mut World::new(PhysicsKind::Aabb), SEED);
+        replay.inputs.truncate(replay.inputs.len() - 20);
+        assert!(verify_replay(&replay).is_err());
+    }
+    /// Whether each force zone is blowing right now.
+    fn gusts(world: &World) -> Vec<bool> {
+        world.physics.force_zones().into_iter().map(|(_, blowing)| blowing).collect()
+    }
+
+    #[test]
+    fn a_daily_played_after_another_level_still_checks_out() {
+        for physics in [PhysicsKind::Aabb, PhysicsKind::NPhysics].iter() {
+            let mut world = World::new(*physics);
+
+            let layout = Layout::new(&mut world);
+            let mut race = Race::new(&mut world, layout, vec!{Controls::Keys(KeySet::single())});
+            for _ in 0..100 {
+                race.step(&mut world, TIME_STEP);
+            }
+            drop(race);
+
+            // Gusts blow as they do on the fresh world replays are checked on
+            let mut fresh = World::new(*physics);
+            let mut daily = Race::daily(&mut world, GUSTY_SEED);
+            let mut fresh_daily = Race::daily(&mut fresh, GUSTY_SEED);
+            for _ in 0..300 {
+                daily.step(&mut world, TIME_STEP);
+                fresh_daily.step(&mut fresh, TIME_STEP);
+                assert_eq!(gusts(&world), gusts(&fresh));
+            }
+            assert_eq!(gusts(&world).len(), 1);
+            drop(daily);
+
+            assert_eq!(verify_replay(&play_daily(&mut world, GUSTY_SEED)), Ok(()));
+        }
+    }
 }
\ No newline at end of file
@@ -1,12 +1,18 @@
-use crate::{graphics, Context, Scene, World, KeyCode, level, util};
+use crate::{graphics, Context, Scene, World, KeyCode, checkpoint::RespawnRules, generator, level, stats, util};
+use std::time::SystemTime;
 extern crate nalgebra as na;
 use na::Point2;
 
 pub struct MenuScene {
     title_text: graphics::Text,
     begin_text: graphics::Text,
+    stats_text: graphics::Text,
 
     is_done: bool,
+    player_count: usize,
+    is_practice: bool,
+    is_daily: bool,
+    show_stats: bool,
 }
 
 impl MenuScene {
@@ -17,15 +23,25 @@ impl MenuScene {
             .font(font)
             .scale(graphics::Scale::uniform(56.0));
 
-        let begin_text_fragment = graphics::TextFragment::new("Press space to begin")
+        let begin_text_fragment = graphics::TextFragment::new("Space to begin, P to practice, 2-4 for multiplayer")
             .color(graphics::WHITE)
             .font(font)
             .scale(graphics::Scale::uniform(36.0));
 
+        let stats_text_fragment = graphics::TextFragment::new("D for the daily challenge, S for statistics")
+            .color(graphics::WHITE)
+            .font(font)
+            .scale(graphics::Scale::uniform(24.0));
+
         Self { 
             title_text: graphics::Text::new(title_text_fragment),
             begin_text: graphics::Text::new(begin_text_fragment),
+            stats_text: graphics::Text::new(stats_text_fragment),
             is_done: false,
+            player_count: 1,
+            is_practice: false,
+            is_daily: false,
+            show_stats: false,
         }
     }
 }
@@ -36,8 +52,18 @@ impl Scene<World> for MenuScene {
         ctx: &mut Context, 
         world: &mut World
     ) -> Option<Box<dyn Scene<World>>> {
-        if self.is_done {
-            let next_scene = level::LevelScene::new(ctx, world);
+        if self.show_stats {
+            Some(Box::new(stats::StatsScene::new(ctx, world)))
+        } else if self.is_done {
+            let next_scene = if self.player_count > 1 {
+                level::LevelScene::local_multiplayer(ctx, world, self.player_count)
+            } else if self.is_daily {
+                level::LevelScene::daily(ctx, world, generator::daily_seed(SystemTime::now()))
+            } else if self.is_practice {
+                level::LevelScene::practice(ctx, world, RespawnRules::default())
+            } else {
+                level::LevelScene::new(ctx, world)
+            };
             Some(Box::new(next_scene))
         } else {
             None
@@ -82,18 +108,52 @@ impl Scene<World> for MenuScene {
                 .dest(begin_text_dest)
        )
       .unwrap();
+
+        let stats_text_width = self.stats_text.width(ctx) as f32;
+
+        let stats_text_dest = util::point_to_old(Point2::new(
+            (window_width / 2.0) - (stats_text_width / 2.0),
+            begin_text_dest.y + begin_text_height + 20.0,
+        ));
+
+        graphics::draw(
+            ctx,
+            &self.stats_text,
+            graphics::DrawParam::new()
+                .dest(stats_text_dest)
+        )
+        .unwrap();
     }
     
     fn input(
         &mut self, 
         _world: &mut World, 
         keycode: KeyCode, 
-        _pressed: bool, 
-        _repeat: bool
+        pressed: bool, 
+        repeat: bool
     ) {
-        if keycode == KeyCode::Space {
-            self.is_done = true;
+        // The key that ended the last scene may still be held or coming up
+        if !pressed || repeat {
+            return;
         }
+
+        if keycode == KeyCode::S {
+            self.show_stats = true;
+            return;
+        }
+
+        let player_count = match keycode {
+            KeyCode::Space | KeyCode::P | KeyCode::D => 1,
+            KeyCode::Key2 => 2,
+            KeyCode::Key3 => 3,
+            KeyCode::Key4 => 4,
+            _ => return,
+        };
+
+        self.player_count = player_count;
+        self.is_practice = keycode == KeyCode::P;
+        self.is_daily = keycode == KeyCode::D;
+        self.is_done = true;
     }
     
     fn name(&self) -> &str {
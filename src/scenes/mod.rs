@@ -1,11 +1,19 @@
 pub mod menu;
 pub mod level;
+pub mod netplay;
+pub mod game_over;
+pub mod stats;
 
 use crate::{Context, KeyCode};
+use ggez::event::{Axis, Button, GamepadId};
 
 pub trait Scene<W> {
     fn update(&mut self, ctx: &mut Context, world: &mut W) -> Option<Box<dyn Scene<W>>>;
     fn draw(&mut self, ctx: &mut Context, world: &mut W);
     fn input(&mut self, world: &mut W, keycode: KeyCode, pressed: bool, repeat: bool);
+    fn gamepad_button(&mut self, _world: &mut W, _id: GamepadId, _button: Button, _pressed: bool) {}
+    fn gamepad_axis(&mut self, _world: &mut W, _id: GamepadId, _axis: Axis, _value: f32) {}
+    /// False while a changed movement profile would spoil a run that has to play back the same.
+    fn allows_hot_reload(&self) -> bool { true }
     fn name(&self) -> &str;
 }
\ No newline at end of file
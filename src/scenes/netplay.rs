@@ -0,0 +1,355 @@
+use crate::{Context, graphics, timer, util, Scene, World, KeyCode, controls::KeySet, gfx::{DrawPawnType, GfxUtil, PawnDrawData}, player::{Player, PlayerInput}, lava::Lava, net::{LoopbackTransport, RollbackSession, Transport}, physics::{PhysicsKind, TIME_STEP}, snapshot::WorldSnapshot, movement::MovementProfile, level::{self, Layout}, game_over};
+use nalgebra::Point2;
+
+use std::collections::VecDeque;
+
+/// Most fixed steps one update catches up on, so a long hitch doesn't freeze the game.
+const MAX_STEPS_PER_UPDATE: usize = 5;
+
+/// The state at the start of a frame, what a rollback goes back to.
+#[derive(Clone)]
+struct SavedFrame {
+    frame: u32,
+    world: WorldSnapshot,
+    /// Players that have sunk into the lava.
+    out: Vec<bool>,
+}
+
+/// One side of a netplay race, with a world of its own.
+struct Peer {
+    world: World,
+    layout: Layout,
+    players: Vec<Player>,
+    lava: Lava,
+    out: Vec<bool>,
+
+    session: RollbackSession,
+    /// From the oldest frame that may still be rolled back to.
+    saved: VecDeque<SavedFrame>,
+    /// First frame that couldn't be saved or rolled back to, everything after it may
+    /// differ from the other peer.
+    lost_frame: Option<u32>,
+    keys: KeySet,
+    input: PlayerInput,
+}
+
+impl Peer {
+    fn new(
+        physics: PhysicsKind,
+        movement: MovementProfile,
+        transport: Box<dyn Transport>,
+        local_player: usize,
+        keys: KeySet,
+    ) -> Self {
+        let mut world = World::new(physics);
+        world.movement = movement;
+        world.apply_movement_profile();
+
+        let layout = Layout::new(&mut world);
+        let players = layout.spawn_players(2, &mut world);
+        let lava = layout.spawn_lava(&mut world);
+
+        Self {
+            world,
+            layout,
+            out: vec!{false; players.len()},
+            players,
+            lava,
+            session: RollbackSession::new(transport, local_player),
+            saved: VecDeque::new(),
+            lost_frame: None,
+            keys,
+            input: Default::default(),
+        }
+    }
+
+    fn save(&mut self, frame: u32) {
+        let world = match WorldSnapshot::capture(&self.world, &self.layout, &self.players, &self.lava) {
+            Some(world) => world,
+            None => {
+                log::error!("Failed to save frame {}, a player has no body", frame);
+                self.lost_frame.get_or_insert(frame);
+                return;
+            },
+        };
+
+        self.saved.push_back(SavedFrame {
+            frame,
+            world,
+            out: self.out.clone(),
+        });
+    }
+
+    fn restore(&mut self, saved: &SavedFrame) {
+        saved.world.restore(&mut self.world, &mut self.layout, &mut self.players, &mut self.lava);
+        self.out = saved.out.clone();
+    }
+
+    /// Runs one frame with a fixed time step, the same inputs always give the same frame.
+    fn simulate(&mut self, inputs: [PlayerInput; 2]) {
+        for (player, input) in self.players.iter_mut().zip(inputs.iter()) {
+            player.input = *input;
+        }
+
+        self.world.physics.step();
+        self.lava.update(TIME_STEP, &mut self.world);
+        for player in self.players.iter_mut() {
+            player.update(TIME_STEP, &mut self.world);
+        }
+
+        // Decided from positions rather than sensor events, which a rollback can lose
+        for (out, player) in self.out.iter_mut().zip(&self.players) {
+            if player.position.y >= self.lava.height {
+                *out = true;
+            }
+        }
+    }
+
+    fn rollback(&mut self, frame: u32) {
+        let index = match self.saved.iter().position(|saved| saved.frame == frame) {
+            Some(index) => index,
+            None => {
+                log::error!("Frame {} was never saved, it can't be rolled back to", frame);
+                self.lost_frame.get_or_insert(frame);
+                return;
+            },
+        };
+        let saved = self.saved[index].clone();
+        self.saved.truncate(index);
+        self.restore(&saved);
+
+        for frame in frame..self.session.frame() {
+            self.save(frame);
+            let inputs = self.session.inputs(frame);
+            self.simulate(inputs);
+        }
+    }
+
+    fn update(&mut self) {
+        if let Some(frame) = self.session.poll() {
+            self.rollback(frame);
+        }
+
+        if self.session.can_advance() {
+            self.save(self.session.frame());
+            let inputs = self.session.advance(self.input);
+            self.simulate(inputs);
+        }
+
+        // Confirmed frames are never rolled back into, only the newest of them is kept
+        let confirmed = self.session.confirmed_frame();
+        while self.saved.front().map(|saved| saved.frame < confirmed).unwrap_or(false) {
+            self.saved.pop_front();
+        }
+    }
+
+    /// Players out as of the last confirmed frame, `None` until it is simulated.
+    fn confirmed_out(&self) -> Option<&[bool]> {
+        let confirmed = self.session.confirmed_frame();
+
+        if self.session.frame() <= confirmed {
+            Some(&self.out)
+        } else {
+            self.saved
+                .front()
+                .filter(|saved| saved.frame == confirmed)
+                .map(|saved| &saved.out[..])
+        }
+    }
+
+    /// Decided once at most one player is left standing, with the index of the winner.
+    /// Only confirmed frames count, so both peers agree on it.
+    fn result(&self) -> Option<Option<usize>> {
+        let out = self.confirmed_out()?;
+        if out.iter().filter(|out| !**out).count() > 1 {
+            return None;
+        }
+
+        Some(out.iter().position(|out| !*out))
+    }
+}
+
+/// A race up the lava against a player on another peer, kept in step with rollback.
+/// Only the AABB backend resimulates exactly, nphysics keeps contact state that
+/// snapshots don't capture and peers can drift apart on it.
+pub struct NetplayScene {
+    /// The first peer is the one shown, a loopback game runs the other one here too.
+    peers: Vec<Peer>,
+    gfx: GfxUtil,
+    /// Seconds not simulated yet.
+    accumulator: f32,
+    desynced: bool,
+    /// Shown over the race and on the game over screen, the result may not be trusted.
+    warnings: Vec<String>,
+}
+
+impl NetplayScene {
+    /// Races a player on the other end of `transport`, who controls the other player.
+    pub fn new(
+        ctx: &mut Context,
+        physics: PhysicsKind,
+        transport: Box<dyn Transport>,
+        local_player: usize,
+        world: &World,
+    ) -> Self {
+        let peer = Peer::new(physics, world.movement, transport, local_player, KeySet::wasd());
+
+        Self::with_peers(ctx, physics, vec!{peer})
+    }
+
+    /// Runs both peers in this process, one on WASD and one on the arrow keys.
+    /// `latency` is in frames, to watch rollbacks happen on one machine.
+    pub fn loopback(ctx: &mut Context, physics: PhysicsKind, latency: usize, world: &World) -> Self {
+        let (a, b) = LoopbackTransport::pair(latency);
+        let peers = vec!{
+            Peer::new(physics, world.movement, Box::new(a), 0, KeySet::wasd()),
+            Peer::new(physics, world.movement, Box::new(b), 1, KeySet::arrows()),
+        };
+
+        Self::with_peers(ctx, physics, peers)
+    }
+
+    fn with_peers(ctx: &mut Context, physics: PhysicsKind, peers: Vec<Peer>) -> Self {
+        let mut warnings = Vec::new();
+        if physics != PhysicsKind::Aabb {
+            log::warn!("Rollbacks are only exact with --physics aabb, peers may drift apart");
+            warnings.push("Rollbacks are only exact with --physics aabb".to_string());
+        }
+
+        Self {
+            peers,
+            gfx: GfxUtil::new(ctx),
+            accumulator: 0.,
+            desynced: false,
+            warnings,
+        }
+    }
+
+    /// A peer that lost a frame has desynced, and the confirmed frames of both
+    /// loopback peers must be identical.
+    fn check_desync(&mut self) {
+        if self.desynced {
+            return;
+        }
+
+        if let Some(frame) = self.peers.iter().filter_map(|peer| peer.lost_frame).min() {
+            self.warnings.push(format!("Lost frame {}, the peers may have desynced", frame));
+            self.desynced = true;
+            return;
+        }
+
+        if self.peers.len() < 2 {
+            return;
+        }
+
+        let a = &self.peers[0];
+        let b = &self.peers[1];
+        if let (Some(saved_a), Some(saved_b)) = (a.saved.front(), b.saved.front()) {
+            let confirmed = saved_a.frame <= a.session.confirmed_frame()
+                && saved_b.frame <= b.session.confirmed_frame();
+
+            if confirmed && saved_a.frame == saved_b.frame && saved_a.world != saved_b.world {
+                log::error!("Peers desynced at frame {}", saved_a.frame);
+                self.warnings.push(format!("The peers desynced at frame {}", saved_a.frame));
+                self.desynced = true;
+            }
+        }
+    }
+}
+
+impl Scene<World> for NetplayScene {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        _world: &mut World
+    ) -> Option<Box<dyn Scene<World>>> {
+        self.accumulator += timer::delta(ctx).as_secs_f32();
+
+        let mut steps = 0;
+        while self.accumulator >= TIME_STEP {
+            if steps == MAX_STEPS_PER_UPDATE {
+                self.accumulator = 0.;
+                break;
+            }
+
+            for peer in self.peers.iter_mut() {
+                peer.update();
+            }
+            self.accumulator -= TIME_STEP;
+            steps += 1;
+        }
+
+        self.check_desync();
+
+        if let Some(winner) = self.peers[0].result() {
+            let title = match winner {
+                Some(player) => format!("Player {} wins", player + 1),
+                None => "Nobody made it".to_string(),
+            };
+            log::info!("{}", title);
+
+            let game_over = game_over::GameOverScene::new(ctx, &title)
+                .with_notes(self.warnings.clone());
+
+            return Some(Box::new(game_over));
+        }
+
+        None
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        _world: &mut World
+    ) {
+        let peer = &self.peers[0];
+        peer.layout.draw(ctx, &peer.world);
+
+        // Tinted like in a local race, players that are out have sunk
+        let pawns = peer.players
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !peer.out[*i])
+            .map(|(i, player)| PawnDrawData {
+                position: player.position,
+                rotation: 0.,
+                pawn_type: DrawPawnType::Player,
+                tint: level::tint(i),
+            });
+        self.gfx.draw_pawns(ctx, pawns);
+
+        level::draw_lava(ctx, &peer.lava);
+
+        for (row, warning) in self.warnings.iter().enumerate() {
+            let fragment = graphics::TextFragment::new(warning.as_str())
+                .color(graphics::Color::from((255, 120, 80, 255)))
+                .scale(graphics::Scale::uniform(18.0));
+
+            graphics::draw(
+                ctx,
+                &graphics::Text::new(fragment),
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(10., 10. + row as f32 * 20.))),
+            )
+            .unwrap();
+        }
+    }
+
+    fn input(
+        &mut self,
+        _world: &mut World,
+        keycode: KeyCode,
+        pressed: bool,
+        repeat: bool
+    ) {
+        for peer in self.peers.iter_mut() {
+            if peer.keys.apply(&mut peer.input, keycode, pressed, repeat) {
+                break;
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Netplay Scene"
+    }
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+use crate::{graphics, Context, Scene, World, KeyCode, util, menu};
+extern crate nalgebra as na;
+use na::Point2;
+
+/// Most recent daily challenges listed.
+const DAILY_LINES: usize = 5;
+
+/// Records kept in the save file, one line per level and recent daily challenge.
+pub struct StatsScene {
+    title_text: graphics::Text,
+    lines: Vec<graphics::Text>,
+
+    is_done: bool,
+}
+
+impl StatsScene {
+    pub fn new(ctx: &mut Context, world: &World) -> Self {
+        let font = graphics::Font::new(ctx, "/DejaVuSerif.ttf").unwrap();
+        let title_text_fragment = graphics::TextFragment::new("Statistics")
+            .color(graphics::Color::from((192, 128, 64, 255)))
+            .font(font)
+            .scale(graphics::Scale::uniform(56.0));
+
+        let mut lines = vec!{format!("Deaths {}", world.save.total_deaths)};
+        if world.save.levels.is_empty() {
+            lines.push("No runs yet".to_string());
+        }
+        for (name, stats) in world.save.levels.iter() {
+            lines.push(format!(
+                "{}: {} runs, best time {:.1}s, max height {:.0}, most coins {}",
+                name, stats.runs, stats.best_time, stats.max_height, stats.most_coins,
+            ));
+        }
+        for record in world.save.daily.iter().rev().take(DAILY_LINES) {
+            if record.finished {
+                lines.push(format!(
+                    "Daily {}: {:.1}s, {:.0} high, {} coins",
+                    record.seed, record.time, record.height, record.coins,
+                ));
+            } else {
+                lines.push(format!("Daily {}: left unfinished", record.seed));
+            }
+        }
+        lines.push("Space to go back".to_string());
+
+        let lines = lines
+            .into_iter()
+            .map(|line| {
+                let fragment = graphics::TextFragment::new(line)
+                    .color(graphics::WHITE)
+                    .font(font)
+                    .scale(graphics::Scale::uniform(24.0));
+
+                graphics::Text::new(fragment)
+            })
+            .collect();
+
+        Self {
+            title_text: graphics::Text::new(title_text_fragment),
+            lines,
+            is_done: false,
+        }
+    }
+}
+
+impl Scene<World> for StatsScene {
+    fn update(
+        &mut self, 
+        ctx: &mut Context, 
+        _world: &mut World
+    ) -> Option<Box<dyn Scene<World>>> {
+        if self.is_done {
+            Some(Box::new(menu::MenuScene::new(ctx)))
+        } else {
+            None
+        }
+    }
+
+    fn draw(
+        &mut self, 
+        ctx: &mut Context, 
+        _world: &mut World
+    ) {
+        let (window_width, _) = graphics::drawable_size(ctx);
+
+        let title_text_width = self.title_text.width(ctx) as f32;
+        let title_text_height = self.title_text.height(ctx) as f32;
+
+        graphics::draw(
+            ctx,
+            &self.title_text,
+            graphics::DrawParam::new()
+                .dest(util::point_to_old(Point2::new(
+                    (window_width / 2.0) - (title_text_width / 2.0),
+                    40.0,
+                )))
+        )
+        .unwrap();
+
+        let mut y = 40.0 + title_text_height + 20.0;
+        for line in self.lines.iter() {
+            graphics::draw(
+                ctx,
+                line,
+                graphics::DrawParam::new()
+                    .dest(util::point_to_old(Point2::new(40.0, y)))
+            )
+            .unwrap();
+            y += line.height(ctx) as f32 + 10.0;
+        }
+    }
+    
+    fn input(
+        &mut self, 
+        _world: &mut World, 
+        keycode: KeyCode, 
+        pressed: bool, 
+        _repeat: bool
+    ) {
+        if pressed && keycode == KeyCode::Space {
+            self.is_done = true;
+        }
+    }
+    
+    fn name(&self) -> &str {
+        "Stats Scene"
+    }
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use crate::pickup::PickupKind;
+use serde::{Deserialize, Serialize};
+
+/// Pickups collected so far, kept in the `World` so every scene can read it.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Default, Debug, Clone, Copy)]
+pub struct Score {
+    pub coins: u32,
+    pub gems: u32,
+    pub power_ups: u32,
+    #[serde(default)]
+    pub abilities: u32,
+}
+
+impl Score {
+    /// Counts every pickup of `kinds`, to tell how many a level holds.
+    pub fn of(kinds: impl Iterator<Item = PickupKind>) -> Self {
+        let mut score = Self::default();
+        for kind in kinds {
+            score.add(kind);
+        }
+
+        score
+    }
+
+    pub fn add(&mut self, kind: PickupKind) {
+        match kind {
+            PickupKind::Coin => self.coins += 1,
+            PickupKind::Gem => self.gems += 1,
+            PickupKind::PowerUp => self.power_ups += 1,
+            PickupKind::Ability => self.abilities += 1,
+        }
+    }
+
+    pub fn count(&self, kind: PickupKind) -> u32 {
+        match kind {
+            PickupKind::Coin => self.coins,
+            PickupKind::Gem => self.gems,
+            PickupKind::PowerUp => self.power_ups,
+            PickupKind::Ability => self.abilities,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        PickupKind::ALL
+            .iter()
+            .map(|kind| self.count(*kind) * kind.points())
+            .sum()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,280 @@
+use std::{fs, path::Path};
+
+use ggez::GameResult;
+use nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+
+use crate::{World, level::Layout, lava::Lava, player::{Ability, Player}, pickup::{Pickup, PickupKind}, power_up::{ActivePowerUps, PowerUpKind}, score::Score, physics::{BodyHandle, BodyKind, ColliderHandle}};
+
+/// A position or velocity, nalgebra's types don't serialize without its serde feature.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default, Clone, Copy)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<Point2<f32>> for Vec2 {
+    fn from(point: Point2<f32>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<Vector2<f32>> for Vec2 {
+    fn from(vector: Vector2<f32>) -> Self {
+        Self {
+            x: vector.x,
+            y: vector.y,
+        }
+    }
+}
+
+impl From<Vec2> for Point2<f32> {
+    fn from(vec: Vec2) -> Self {
+        Point2::new(vec.x, vec.y)
+    }
+}
+
+impl From<Vec2> for Vector2<f32> {
+    fn from(vec: Vec2) -> Self {
+        Vector2::new(vec.x, vec.y)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct BodySnapshot {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// A body that isn't static, found again by its handle.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct PhysicsBodySnapshot {
+    pub handle: BodyHandle,
+    pub body: BodySnapshot,
+}
+
+/// A grapple in use, hooked again at `point` when it is restored.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct RopeSnapshot {
+    pub hook: ColliderHandle,
+    pub length: f32,
+    pub point: Vec2,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct PickupSnapshot {
+    pub kind: PickupKind,
+    #[serde(default)]
+    pub power_up: Option<PowerUpKind>,
+    #[serde(default)]
+    pub ability: Option<Ability>,
+    pub position: Vec2,
+}
+
+/// Everything the player controller remembers between frames. Held keys are
+/// left out, they belong to whoever is playing when the snapshot is restored.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PlayerSnapshot {
+    pub double_jump: bool,
+    pub air_dash: bool,
+    pub grapple: bool,
+    pub has_jumped: bool,
+    pub jump_was_pressed: bool,
+    pub time_since_grounded: f32,
+    pub time_since_jump_pressed: f32,
+    pub wall_jump_lock: f32,
+    pub facing: f32,
+    pub can_double_jump: bool,
+    pub can_dash: bool,
+    pub dash_was_pressed: bool,
+    pub dash_time: f32,
+    pub dash_cooldown: f32,
+    pub grapple_was_pressed: bool,
+    // toml wants nested tables after every plain value
+    #[serde(default)]
+    pub checkpoint: Option<Vec2>,
+    pub dash_direction: Vec2,
+    pub body: BodySnapshot,
+    #[serde(default)]
+    pub power_ups: ActivePowerUps,
+    #[serde(default)]
+    pub rope: Option<RopeSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct LavaSnapshot {
+    pub height: f32,
+    pub rise_speed: f32,
+    #[serde(default)]
+    pub frozen_for: f32,
+}
+
+/// The state of a running level, in memory for quick-loads or on disk to
+/// attach to bug reports. The layout itself isn't kept, only what changes in it.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct WorldSnapshot {
+    /// Physics steps taken when the snapshot was made.
+    pub ticks: usize,
+    /// Seconds of physics time, decides where the gusts of every force zone are
+    /// in their cycle, which is all the state a force zone has.
+    pub time: f32,
+    /// Whether each of the layout's checkpoints has been reached.
+    #[serde(default)]
+    pub checkpoints: Vec<bool>,
+    /// In the order the scene spawned them.
+    pub players: Vec<PlayerSnapshot>,
+    pub lava: LavaSnapshot,
+    #[serde(default)]
+    pub score: Score,
+    /// Only the ones not collected yet.
+    #[serde(default)]
+    pub pickups: Vec<PickupSnapshot>,
+    /// Every body that can move, the players' and the lava's among them.
+    #[serde(default)]
+    pub bodies: Vec<PhysicsBodySnapshot>,
+}
+
+impl WorldSnapshot {
+    /// `None` if one of the players has no body.
+    pub fn capture<'a>(
+        world: &World,
+        layout: &Layout,
+        players: impl IntoIterator<Item = &'a Player>,
+        lava: &Lava,
+    ) -> Option<Self> {
+        let players: Option<Vec<_>> = players
+            .into_iter()
+            .map(|player| player.snapshot(world))
+            .collect();
+
+        let bodies = world.physics.bodies()
+            .into_iter()
+            .filter(|(_, kind)| *kind != BodyKind::Static)
+            .filter_map(|(handle, _)| Some(PhysicsBodySnapshot {
+                handle,
+                body: BodySnapshot {
+                    position: world.physics.get_position(handle)?.into(),
+                    velocity: world.physics.get_velocity(handle)?.into(),
+                },
+            }))
+            .collect();
+
+        Some(Self {
+            ticks: world.physics.ticks(),
+            time: world.physics.time(),
+            checkpoints: layout.checkpoints.iter().map(|checkpoint| checkpoint.is_reached).collect(),
+            players: players?,
+            lava: lava.snapshot(),
+            score: world.score,
+            pickups: layout.pickups.iter().map(Pickup::snapshot).collect(),
+            bodies,
+        })
+    }
+
+    /// Players are matched up in order, ones out since the snapshot stay out.
+    /// Pickups collected since the snapshot come back.
+    pub fn restore<'a>(
+        &self,
+        world: &mut World,
+        layout: &mut Layout,
+        players: impl IntoIterator<Item = &'a mut Player>,
+        lava: &mut Lava,
+    ) {
+        world.physics.set_clock(self.ticks, self.time);
+        for body in self.bodies.iter() {
+            world.physics.set_position(body.handle, body.body.position.into());
+            world.physics.set_velocity(body.handle, body.body.velocity.into());
+        }
+
+        for (checkpoint, is_reached) in layout.checkpoints.iter_mut().zip(&self.checkpoints) {
+            checkpoint.is_reached = *is_reached;
+        }
+
+        // Pickups still there are kept, the rest are dropped and their bodies removed
+        let mut pickups = std::mem::take(&mut layout.pickups);
+        layout.pickups = self.pickups
+            .iter()
+            .map(|snapshot| {
+                match pickups.iter().position(|pickup| pickup.snapshot() == *snapshot) {
+                    Some(index) => pickups.remove(index),
+                    None => Pickup::from_snapshot(snapshot, world),
+                }
+            })
+            .collect();
+
+        for (player, snapshot) in players.into_iter().zip(&self.players) {
+            player.restore(snapshot, world);
+        }
+        lava.restore(&self.lava, world);
+        world.score = self.score;
+    }
+
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(toml::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{PhysicsKind, TIME_STEP};
+
+    fn simulate(world: &mut World, players: &mut [Player], lava: &mut Lava, frames: usize) {
+        for _ in 0..frames {
+            world.physics.step();
+            lava.update(TIME_STEP, world);
+            for player in players.iter_mut() {
+                player.update(TIME_STEP, world);
+            }
+        }
+    }
+
+    #[test]
+    fn restoring_a_snapshot_brings_everything_back() {
+        let mut world = World::new(PhysicsKind::Aabb);
+        let mut layout = Layout::new(&mut world);
+        let mut players = vec!{Player::spawn_at(Point2::new(0., 200.), &mut world)};
+        let mut lava = Lava::new(320., 1600., layout.lava_rise_speed, &mut world);
+
+        // Hooked to the floor below and swinging to the right
+        players[0].abilities.grapple = true;
+        players[0].input.down = true;
+        players[0].input.grapple = true;
+        simulate(&mut world, &mut players, &mut lava, 1);
+        players[0].input = Default::default();
+        players[0].input.right = true;
+        simulate(&mut world, &mut players, &mut lava, 30);
+        assert!(players[0].is_grappling());
+
+        let snapshot = WorldSnapshot::capture(&world, &layout, &players, &lava).unwrap();
+        let saved: WorldSnapshot = toml::from_str(&toml::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(saved, snapshot);
+
+        // Let go, collect a pickup, reach a checkpoint and keep going
+        players[0].input.grapple = true;
+        layout.pickups.pop();
+        world.score.add(PickupKind::Coin);
+        layout.checkpoints[0].is_reached = true;
+        simulate(&mut world, &mut players, &mut lava, 60);
+        assert!(!players[0].is_grappling());
+
+        saved.restore(&mut world, &mut layout, &mut players, &mut lava);
+
+        assert!(players[0].is_grappling());
+        assert_eq!(WorldSnapshot::capture(&world, &layout, &players, &lava).unwrap(), snapshot);
+    }
+}
\ No newline at end of file
@@ -27,6 +27,14 @@ pub fn point_to_velocity<N: na::RealField + Copy + Scalar>(point: Point2<N>) ->
     Velocity2::linear(point.x, point.y)
 }
 
+pub fn pixels_to_meters<T: std::ops::Div<f32, Output = T>>(pixels: T, pixels_per_meter: f32) -> T {
+    pixels / pixels_per_meter
+}
+
+pub fn meters_to_pixels<T: std::ops::Mul<f32, Output = T>>(meters: T, pixels_per_meter: f32) -> T {
+    meters * pixels_per_meter
+}
+
 pub fn add<N: std::ops::Add<Output = N> + Copy + Scalar>(
     point1: Point2<N>,
     point2: Point2<N>,
@@ -39,4 +47,13 @@ pub fn sub<N: std::ops::Sub<Output = N> + Copy + Scalar>(
     point2: Point2<N>,
 ) -> Point2<N> {
     Point2::new(point1.x - point2.x, point1.y - point2.y)
+}
+
+/// Moves `current` towards `target` by at most `max_delta`, without overshooting.
+pub fn approach(current: f32, target: f32, max_delta: f32) -> f32 {
+    if current < target {
+        (current + max_delta).min(target)
+    } else {
+        (current - max_delta).max(target)
+    }
 }
\ No newline at end of file
@@ -1,13 +1,55 @@
-use crate::{physics};
+use crate::{physics::{PhysicsBackend, PhysicsKind}, entity::EntityAllocator, leaderboard::LeaderboardClient, movement::MovementProfile, notice::Notices, power_up::PowerUpTable, save::SaveData, score::Score};
+use nalgebra::Vector2;
 
 pub struct World {
-    pub physics: physics::Physics2D,
+    pub physics: Box<dyn PhysicsBackend>,
+    /// Which backend `physics` is, a replay only plays back the same on that one.
+    pub physics_kind: PhysicsKind,
+    pub movement: MovementProfile,
+    /// What `apply_movement_profile` last handed to the physics.
+    applied_movement: Option<MovementProfile>,
+    pub entities: EntityAllocator,
+    pub power_ups: PowerUpTable,
+    /// Of the level being played, reset when one starts.
+    pub score: Score,
+    /// Loaded at startup, scenes write it back when something changes.
+    pub save: SaveData,
+    /// `None` when the leaderboard is turned off.
+    pub leaderboard: Option<LeaderboardClient>,
+    pub notices: Notices,
 }
 
 impl World {
-    pub fn new() -> Self {
-        Self {
-            physics: physics::Physics2D::new(),
+    pub fn new(physics: PhysicsKind) -> Self {
+        let mut world = Self {
+            physics: physics.create(),
+            physics_kind: physics,
+            movement: MovementProfile::default(),
+            applied_movement: None,
+            entities: EntityAllocator::default(),
+            power_ups: PowerUpTable::default(),
+            score: Score::default(),
+            save: SaveData::default(),
+            leaderboard: None,
+            notices: Notices::default(),
+        };
+        world.apply_movement_profile();
+
+        world
+    }
+
+    /// Only passes on the values the profile changed since the last call, so a hot
+    /// reload doesn't undo gravity that was set on the physics while playing.
+    pub fn apply_movement_profile(&mut self) {
+        let applied = self.applied_movement;
+
+        if applied.map(|applied| applied.gravity) != Some(self.movement.gravity) {
+            self.physics.set_gravity(Vector2::new(0., self.movement.gravity));
+        }
+        if applied.map(|applied| applied.max_slope) != Some(self.movement.max_slope) {
+            self.physics.set_max_slope(self.movement.max_slope.to_radians());
         }
+
+        self.applied_movement = Some(self.movement);
     }
 }
\ No newline at end of file